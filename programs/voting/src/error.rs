@@ -127,4 +127,175 @@ pub enum VotingError {
     
     #[msg("Arithmetic underflow")]
     ArithmeticUnderflow,
+
+    #[msg("Invalid verifying key")]
+    InvalidVerifyingKey,
+
+    #[msg("Circuit id must be between 1 and 32 characters")]
+    CircuitIdTooLong,
+
+    #[msg("Verifying key has not been finalized")]
+    VerifyingKeyNotFinalized,
+
+    #[msg("Verifying key is finalized and can no longer be modified")]
+    VerifyingKeyFinalized,
+
+    #[msg("Ballot type does not match the poll type")]
+    InvalidBallotType,
+
+    #[msg("Ranking must list distinct poll options")]
+    InvalidRanking,
+
+    #[msg("Tally account is required for this poll type")]
+    MissingTallyAccount,
+
+    #[msg("Poll has not been closed yet")]
+    PollNotClosed,
+
+    #[msg("Tally is already complete")]
+    TallyAlreadyComplete,
+
+    #[msg("Ballot account does not belong to this poll")]
+    InvalidBallotAccount,
+
+    #[msg("Number of selected options is outside the poll's limits")]
+    InvalidSelectionCount,
+
+    #[msg("This poll only accepts committed ballots")]
+    BallotMustBeCommitted,
+
+    #[msg("Poll is not in its reveal window")]
+    NotInRevealPhase,
+
+    #[msg("Ballot has already been revealed")]
+    BallotAlreadyRevealed,
+
+    #[msg("Revealed ballot does not match the commitment")]
+    InvalidCommitment,
+
+    #[msg("This poll only accepts encrypted ballots")]
+    BallotMustBeEncrypted,
+
+    #[msg("Ciphertext is not a valid curve point")]
+    InvalidCiphertext,
+
+    #[msg("Ballot encryption proof failed verification")]
+    InvalidEncryptionProof,
+
+    #[msg("Invalid trustee configuration")]
+    InvalidTrusteeConfiguration,
+
+    #[msg("Invalid decryption share")]
+    InvalidDecryptionShare,
+
+    #[msg("Decryption share has already been submitted")]
+    DecryptionShareAlreadySubmitted,
+
+    #[msg("Not enough decryption shares to decrypt the tally")]
+    NotEnoughDecryptionShares,
+
+    #[msg("Poll cannot move to the requested status")]
+    InvalidStatusTransition,
+
+    #[msg("Poll has not reached its begin time")]
+    PollNotStarted,
+
+    #[msg("No delegations were provided")]
+    NoActiveDelegations,
+
+    #[msg("Too many delegations for a single ballot")]
+    TooManyDelegations,
+
+    #[msg("Delegation account does not belong to this poll or delegatee")]
+    InvalidDelegationAccount,
+
+    #[msg("Delegation has already been used")]
+    DelegationAlreadyUsed,
+
+    #[msg("Delegation chain exceeds the poll's maximum depth")]
+    DelegationTooDeep,
+
+    #[msg("Delegation would create a cycle")]
+    DelegationCycle,
+
+    #[msg("Delegation route account does not match the chain")]
+    InvalidDelegationRoute,
+
+    #[msg("Too many tags, or a tag is empty or too long")]
+    InvalidTags,
+
+    #[msg("Standing delegation does not apply to this poll")]
+    StandingDelegationNotApplicable,
+
+    #[msg("Poll is paused")]
+    PollPaused,
+
+    #[msg("Already paused")]
+    AlreadyPaused,
+
+    #[msg("Not paused")]
+    NotPaused,
+
+    #[msg("This action must go through a governance proposal")]
+    GovernanceRequired,
+
+    #[msg("Invalid admin set")]
+    InvalidAdminSet,
+
+    #[msg("Signer is not a governance admin")]
+    NotAnAdmin,
+
+    #[msg("Proposal has already been approved by this admin")]
+    ProposalAlreadyApproved,
+
+    #[msg("Proposal is not approved or its timelock has not passed")]
+    ProposalNotExecutable,
+
+    #[msg("Proposal has already been executed")]
+    ProposalAlreadyExecuted,
+
+    #[msg("Proposal was made for a previous admin set")]
+    ProposalStale,
+
+    #[msg("Proposal action does not match this instruction")]
+    ProposalActionMismatch,
+
+    #[msg("This governance action cannot be executed here")]
+    InvalidGovernanceAction,
+
+    #[msg("Signer is not the pending authority")]
+    NotPendingAuthority,
+
+    #[msg("Badge has been revoked")]
+    BadgeNotActive,
+
+    #[msg("Badge token accounts are missing")]
+    MissingBadgeTokenAccounts,
+
+    #[msg("Invalid badge token account")]
+    InvalidBadgeTokenAccount,
+
+    #[msg("Voter does not hold the token this poll is gated on")]
+    TokenGateNotMet,
+
+    #[msg("Invalid token gate account")]
+    InvalidTokenGateAccount,
+
+    #[msg("Balance proof does not match the poll's snapshot")]
+    InvalidWeightProof,
+
+    #[msg("Invalid amount of tokens to lock")]
+    InvalidLockAmount,
+
+    #[msg("Escrow token accounts are missing or invalid")]
+    InvalidEscrowAccount,
+
+    #[msg("Tokens stay locked until the poll is closed or cancelled")]
+    TokensStillLocked,
+
+    #[msg("No tokens are locked for this vote")]
+    NothingLocked,
+
+    #[msg("Voter is not in the poll's allowlist")]
+    InvalidMembershipProof,
 }
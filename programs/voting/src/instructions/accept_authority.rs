@@ -0,0 +1,44 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::error::*;
+
+// Shared by the global config and polls; `account` is the one whose authority changed
+#[event]
+pub struct AuthorityTransferred {
+    pub account: Pubkey,
+    pub previous_authority: Pubkey,
+    pub new_authority: Pubkey,
+    pub transferred_at: i64,
+}
+
+#[derive(Accounts)]
+pub struct AcceptAuthority<'info> {
+    #[account(
+        mut,
+        seeds = [b"voting"],
+        bump = voting.bump,
+        constraint = voting.pending_authority == Some(new_authority.key()) @ VotingError::NotPendingAuthority
+    )]
+    pub voting: Account<'info, Voting>,
+    pub new_authority: Signer<'info>,
+}
+
+pub fn handler(ctx: Context<AcceptAuthority>) -> Result<()> {
+    // no `ensure_operational`: a compromised authority must still be replaceable while paused
+    let voting = &mut ctx.accounts.voting;
+    let moment = Clock::get()?.unix_timestamp;
+
+    let previous_authority = voting.authority;
+    voting.authority = ctx.accounts.new_authority.key();
+    voting.pending_authority = None;
+
+    emit!(AuthorityTransferred {
+        account: voting.key(),
+        previous_authority,
+        new_authority: voting.authority,
+        transferred_at: moment,
+    });
+
+    msg!("Global authority transferred to {}", voting.authority);
+    Ok(())
+}
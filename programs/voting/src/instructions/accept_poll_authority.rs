@@ -0,0 +1,35 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::error::*;
+use crate::instructions::accept_authority::AuthorityTransferred;
+
+#[derive(Accounts)]
+pub struct AcceptPollAuthority<'info> {
+    #[account(mut, constraint = poll.pending_authority == Some(new_authority.key()) @ VotingError::NotPendingAuthority)]
+    pub poll: Account<'info, Poll>,
+    #[account(seeds = [b"voting"], bump = voting.bump)]
+    pub voting: Account<'info, Voting>,
+    pub new_authority: Signer<'info>,
+}
+
+pub fn handler(ctx: Context<AcceptPollAuthority>) -> Result<()> {
+    ensure_operational(&ctx.accounts.voting, Some(&ctx.accounts.poll))?;
+
+    let poll = &mut ctx.accounts.poll;
+    let moment = Clock::get()?.unix_timestamp;
+
+    let previous_authority = poll.authority;
+    poll.authority = ctx.accounts.new_authority.key();
+    poll.pending_authority = None;
+    poll.updated_at = moment;
+
+    emit!(AuthorityTransferred {
+        account: poll.key(),
+        previous_authority,
+        new_authority: poll.authority,
+        transferred_at: moment,
+    });
+
+    msg!("Poll {} transferred to {}", poll.poll_name, poll.authority);
+    Ok(())
+}
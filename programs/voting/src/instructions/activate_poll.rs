@@ -0,0 +1,30 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::error::*;
+
+#[derive(Accounts)]
+pub struct ActivatePoll<'info> {
+    #[account(mut, constraint = poll.status.can_transition_to(PollStatus::Active) @ VotingError::InvalidStatusTransition)]
+    pub poll: Account<'info, Poll>,
+    #[account(seeds = [b"voting"], bump = voting.bump)]
+    pub voting: Account<'info, Voting>,
+
+    pub caller: Signer<'info>,
+}
+
+// Permissionless: anyone may open a pending poll once its begin time has passed
+pub fn handler(ctx: Context<ActivatePoll>) -> Result<()> {
+    ensure_operational(&ctx.accounts.voting, Some(&ctx.accounts.poll))?;
+
+    let poll = &mut ctx.accounts.poll;
+    let moment = Clock::get()?.unix_timestamp;
+
+    require!(moment >= poll.poll_begin_time, VotingError::PollNotStarted);
+    require!(moment <= poll.poll_end_time, VotingError::VotingEnded);
+
+    let poll_key = poll.key();
+    poll.transition(poll_key, PollStatus::Active, None, moment)?;
+
+    msg!("Poll activated: {}", poll.poll_name);
+    Ok(())
+}
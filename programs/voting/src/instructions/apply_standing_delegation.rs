@@ -0,0 +1,106 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::error::*;
+use crate::utils::*;
+
+#[event]
+pub struct StandingDelegationApplied {
+    pub poll: Pubkey,
+    pub standing_delegation: Pubkey,
+    pub delegator: Pubkey,
+    pub delegatee: Pubkey,
+    pub chain_length: u8,
+    pub applied_at: i64,
+}
+
+#[derive(Accounts)]
+#[instruction(zk_proof: Vec<u8>, nullifier_hash: [u8; 32])]
+pub struct ApplyStandingDelegation<'info> {
+    #[account(constraint = poll.is_active() @ VotingError::PollNotActive)]
+    pub poll: Account<'info, Poll>,
+    #[account(seeds = [b"voting"], bump = voting.bump)]
+    pub voting: Account<'info, Voting>,
+    #[account(address = standing_delegation.derivation_key @ VotingError::InvalidVerifyingKey)]
+    pub derivation_key: Account<'info, VerifyingKey>,
+    #[account(constraint = standing_delegation.delegatee == delegatee.key() @ VotingError::UnauthorizedAccess)]
+    pub standing_delegation: Account<'info, StandingDelegation>,
+    // Keyed by the delegator's nullifier in this poll, like a regular delegation, so that
+    // `cast_vote` withdraws it or refuses the vote once the delegatee has used it
+    #[account(init, payer = delegatee, space = VoteDelegation::MAX_SIZE, seeds = [b"delegation", poll.key().as_ref(), &nullifier_hash], bump)]
+    pub vote_delegation: Account<'info, VoteDelegation>,
+    // Fails if the delegator already delegated in this poll, which takes precedence
+    #[account(init, payer = delegatee, space = DelegationRoute::MAX_SIZE, seeds = [b"delegation_route", poll.key().as_ref(), standing_delegation.delegator.as_ref()], bump)]
+    pub delegation_route: Account<'info, DelegationRoute>,
+    /// CHECK: only checked to be empty, the address is fixed by the seeds
+    #[account(seeds = [b"nullifier", poll.key().as_ref(), &nullifier_hash], bump)]
+    pub voter_nullifier: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub delegatee: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+// Sent by the delegatee, the delegator takes no part: turns a matching standing delegation into
+// a regular `VoteDelegation` that `cast_delegated_vote` counts. The proof is checked against the
+// delegation's `derivation_key` with public inputs
+//   [nullifier_hash, poll_binding(poll), standing_delegation.nullifier_hash]
+// i.e. `nullifier_hash` is the delegator's nullifier in this poll. A delegator who already voted
+// here keeps their ballot. The delegatee's chain is passed as remaining accounts, as for
+// `delegate_vote`.
+pub fn handler(ctx: Context<ApplyStandingDelegation>, zk_proof: Vec<u8>, nullifier_hash: [u8; 32]) -> Result<()> {
+    ensure_operational(&ctx.accounts.voting, Some(&ctx.accounts.poll))?;
+
+    let poll = &ctx.accounts.poll;
+    let standing_delegation = &ctx.accounts.standing_delegation;
+    let vote_delegation = &mut ctx.accounts.vote_delegation;
+    let delegation_route = &mut ctx.accounts.delegation_route;
+    let moment = Clock::get()?.unix_timestamp;
+
+    require!(poll.max_delegation_depth > 0, VotingError::OperationNotAllowed);
+    require!(moment <= poll.poll_end_time, VotingError::VotingEnded);
+    require!(standing_delegation.applies_to(poll, moment), VotingError::StandingDelegationNotApplicable);
+    require!(ctx.accounts.voter_nullifier.data_is_empty(), VotingError::VoterAlreadyVoted);
+    require!(!zk_proof.is_empty(), VotingError::InvalidZkProof);
+    require!(
+        verify_zk_proof(
+            &zk_proof,
+            &[standing_delegation.nullifier_hash],
+            &ctx.accounts.derivation_key.groth16()?,
+            &poll.key(),
+            &nullifier_hash,
+        )?,
+        VotingError::ZkProofVerificatiionFailed
+    );
+
+    let chain_length = DelegationRoute::chain_length(
+        &poll.key(),
+        &standing_delegation.delegator,
+        &standing_delegation.delegatee,
+        poll.max_delegation_depth,
+        ctx.remaining_accounts,
+    )?;
+
+    vote_delegation.set_inner(standing_delegation.poll_delegation(
+        poll.key(),
+        poll.poll_end_time,
+        moment,
+        ctx.bumps.vote_delegation,
+    ));
+
+    delegation_route.poll = poll.key();
+    delegation_route.delegator = standing_delegation.delegator;
+    delegation_route.delegatee = standing_delegation.delegatee;
+    delegation_route.delegation = vote_delegation.key();
+    delegation_route.bump = ctx.bumps.delegation_route;
+
+    emit!(StandingDelegationApplied {
+        poll: poll.key(),
+        standing_delegation: standing_delegation.key(),
+        delegator: standing_delegation.delegator,
+        delegatee: standing_delegation.delegatee,
+        chain_length,
+        applied_at: moment,
+    });
+
+    msg!("Standing delegation applied to poll: {}", poll.poll_name);
+    Ok(())
+}
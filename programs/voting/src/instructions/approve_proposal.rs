@@ -0,0 +1,43 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+
+#[event]
+pub struct ProposalApproved {
+    pub proposal: Pubkey,
+    pub id: u64,
+    pub admin: Pubkey,
+    pub approvals: u8,
+    pub threshold: u8,
+    pub executable_at: Option<i64>,
+}
+
+#[derive(Accounts)]
+pub struct ApproveProposal<'info> {
+    #[account(seeds = [b"voting"], bump = voting.bump)]
+    pub voting: Account<'info, Voting>,
+    #[account(mut, seeds = [b"proposal", &proposal.id.to_le_bytes()], bump = proposal.bump)]
+    pub proposal: Account<'info, GovernanceProposal>,
+    pub admin: Signer<'info>,
+}
+
+pub fn handler(ctx: Context<ApproveProposal>) -> Result<()> {
+    // no `ensure_operational`: governance stays usable while paused so a `Resume` can pass
+    let voting = &ctx.accounts.voting;
+    let proposal = &mut ctx.accounts.proposal;
+    let admin = ctx.accounts.admin.key();
+    let moment = Clock::get()?.unix_timestamp;
+
+    proposal.approve(voting, &admin, moment)?;
+
+    emit!(ProposalApproved {
+        proposal: proposal.key(),
+        id: proposal.id,
+        admin,
+        approvals: proposal.approval_count(),
+        threshold: voting.threshold,
+        executable_at: proposal.executable_at(voting.timelock),
+    });
+
+    msg!("Governance proposal {} approved ({}/{})", proposal.id, proposal.approval_count(), voting.threshold);
+    Ok(())
+}
@@ -1,4 +1,9 @@
 use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use anchor_spl::associated_token::{self, get_associated_token_address_with_program_id, AssociatedToken};
+use anchor_spl::token_2022::spl_token_2022::{extension::ExtensionType, instruction::AuthorityType, state::Mint};
+use anchor_spl::token_2022_extensions as token_extensions;
+use anchor_spl::token_interface::{self, Token2022};
 use crate::state::*;
 use crate::error::*;
 
@@ -7,44 +12,362 @@ pub struct BadgeAwarded {
     pub recipient: Pubkey,
     pub badge_type: BadgeType,
     pub data: String,
+    pub mint: Option<Pubkey>,
     pub awarded_at: i64
 }
 
 #[derive(Accounts)]
 #[instruction(badge_type: BadgeType, recipient: Pubkey)]
 pub struct AwardBadge<'info> {
-    #[account(init, payer = authority, space = UserBadge::MAX_SIZE, seeds = [b"badge", recipient.as_ref(), &badge_type as u8], bump)]
+    #[account(init, payer = authority, space = UserBadge::MAX_SIZE, seeds = [b"badge", recipient.as_ref(), &[badge_type as u8]], bump)]
     pub user_badge: Account<'info, UserBadge>,
-    #[account(mut, seeds = [b"voting", bump = voting.bump], constraint = voting.authority == authority.key() @ VotingError::UnauthorizedAccess)]
+    #[account(mut, seeds = [b"voting"], bump = voting.bump)]
     pub voting: Account<'info, Voting>,
 
+    // Required once an admin set is configured: an approved `AwardBadge` proposal for this badge
+    #[account(mut, seeds = [b"proposal", &proposal.id.to_le_bytes()], bump = proposal.bump)]
+    pub proposal: Option<Account<'info, GovernanceProposal>>,
+
     #[account(mut)]
     pub authority: Signer<'info>,
 
-    pub recipient: UncheckedAccount<'info>, // this perform checks if the recipient pubkey is validated in seeds.
+    /// CHECK: only the address is used, as the owner of the badge token account
+    #[account(address = recipient @ VotingError::UnauthorizedAccess)]
+    pub recipient_wallet: UncheckedAccount<'info>,
+
+    // Only needed when a soulbound token is minted (`token_uri` given)
+    /// CHECK: created here as a Token-2022 mint, the address is fixed by the seeds
+    #[account(mut, seeds = [b"badge_mint", recipient.as_ref(), &[badge_type as u8]], bump)]
+    pub badge_mint: Option<UncheckedAccount<'info>>,
+    /// CHECK: checked against the recipient's associated token address and created here
+    #[account(mut)]
+    pub recipient_token_account: Option<UncheckedAccount<'info>>,
+    pub token_program: Option<Program<'info, Token2022>>,
+    pub associated_token_program: Option<Program<'info, AssociatedToken>>,
+
     pub system_program: Program<'info, System>,
 }
 
-pub fn handler(ctx: Context<AwardBadge>, badge_type: BadgeType, data: String, recipient: Pubkey) -> Result<()> {
-    let user_badge = &mut ctx.accounts.user_badge;
+pub fn handler(ctx: Context<AwardBadge>, badge_type: BadgeType, recipient: Pubkey, data: String, token_uri: Option<String>) -> Result<()> {
+    ensure_operational(&ctx.accounts.voting, None)?;
+
     let moment = Clock::get()?.unix_timestamp;
 
     require!(data.len() <= 200, VotingError::DataTooLong); // length validation of data
+    if let Some(ref uri) = token_uri {
+        require!(uri.len() <= MAX_BADGE_URI_LENGTH, VotingError::DataTooLong);
+    }
+
+    {
+        let voting = &ctx.accounts.voting;
+        if voting.has_governance() {
+            let proposal = ctx.accounts.proposal.as_mut().ok_or(VotingError::GovernanceRequired)?;
+            let expected = GovernanceAction::AwardBadge {
+                badge_type,
+                recipient,
+                data: data.clone(),
+                token_uri: token_uri.clone(),
+            };
+            require!(proposal.action == expected, VotingError::ProposalActionMismatch);
+            proposal.execute(voting, moment)?;
+        } else {
+            voting.ensure_single_authority(&ctx.accounts.authority.key())?;
+        }
+    }
+
+    let mint = match token_uri {
+        Some(uri) => {
+            let mint_bump = ctx.bumps.badge_mint.ok_or(VotingError::MissingBadgeTokenAccounts)?;
+            Some(mint_badge_token(ctx.accounts, badge_type, recipient, uri, mint_bump)?)
+        },
+        None => None,
+    };
 
     // badge initiallisation
-    user_badge.badge_type = badge_type.clone();
-    user_badge.data = data;
+    let user_badge = &mut ctx.accounts.user_badge;
+    user_badge.badge_type = badge_type;
+    user_badge.data = data.clone();
     user_badge.recipient = recipient;
     user_badge.awarded_at = moment;
     user_badge.is_active = true;
     user_badge.bump = ctx.bumps.user_badge;
+    user_badge.mint = mint;
 
     emit!(BadgeAwarded {
-        recipient: recipient,
-        badge_type: badge_type.clone(),
-        data: data.clone(),
+        recipient,
+        badge_type,
+        data,
+        mint,
         awarded_at: moment,
     });
     msg!("Badge {:?} awarded to {}", badge_type, recipient);
     Ok(())
-}
\ No newline at end of file
+}
+
+// Mints the badge as a single Token-2022 token into the recipient's associated token account.
+// The mint is non-transferable, carries its own metadata and has the config PDA as permanent
+// delegate so `revoke_badge` can burn it; minting authority is dropped so supply stays at one.
+fn mint_badge_token(
+    accounts: &AwardBadge,
+    badge_type: BadgeType,
+    recipient: Pubkey,
+    uri: String,
+    mint_bump: u8,
+) -> Result<Pubkey> {
+    let (mint_info, token_account, token_program, associated_token_program) = match (
+        accounts.badge_mint.as_ref(),
+        accounts.recipient_token_account.as_ref(),
+        accounts.token_program.as_ref(),
+        accounts.associated_token_program.as_ref(),
+    ) {
+        (Some(mint), Some(token_account), Some(token_program), Some(associated_token_program)) => {
+            (mint.to_account_info(), token_account.to_account_info(), token_program.to_account_info(), associated_token_program.to_account_info())
+        },
+        _ => return err!(VotingError::MissingBadgeTokenAccounts),
+    };
+    require!(
+        token_account.key() == get_associated_token_address_with_program_id(&recipient, &mint_info.key(), &token_program.key()),
+        VotingError::InvalidBadgeTokenAccount
+    );
+
+    let voting_info = accounts.voting.to_account_info();
+    let voting_key = voting_info.key();
+    let voting_seeds: &[&[u8]] = &[b"voting", &[accounts.voting.bump]];
+    let badge_seed = [badge_type as u8];
+    let mint_seeds: &[&[u8]] = &[b"badge_mint", recipient.as_ref(), &badge_seed, &[mint_bump]];
+
+    let name = UserBadge::token_name(badge_type);
+    let space = ExtensionType::try_calculate_account_len::<Mint>(&[
+        ExtensionType::NonTransferable,
+        ExtensionType::PermanentDelegate,
+        ExtensionType::MetadataPointer,
+    ])?;
+    // the metadata is written after the mint is initialized and needs its rent up front
+    let lamports = Rent::get()?.minimum_balance(space + UserBadge::token_metadata_len(&name, &uri));
+
+    create_pda_account(
+        &accounts.system_program.to_account_info(),
+        &accounts.authority.to_account_info(),
+        &mint_info,
+        mint_seeds,
+        lamports,
+        space as u64,
+        &token_program.key(),
+    )?;
+
+    // extensions must be initialized before the mint itself
+    token_extensions::non_transferable_mint_initialize(CpiContext::new(
+        token_program.clone(),
+        token_extensions::NonTransferableMintInitialize {
+            token_program_id: token_program.clone(),
+            mint: mint_info.clone(),
+        },
+    ))?;
+    token_extensions::permanent_delegate_initialize(
+        CpiContext::new(
+            token_program.clone(),
+            token_extensions::PermanentDelegateInitialize {
+                token_program_id: token_program.clone(),
+                mint: mint_info.clone(),
+            },
+        ),
+        &voting_key,
+    )?;
+    token_extensions::metadata_pointer_initialize(
+        CpiContext::new(
+            token_program.clone(),
+            token_extensions::MetadataPointerInitialize {
+                token_program_id: token_program.clone(),
+                mint: mint_info.clone(),
+            },
+        ),
+        Some(voting_key),
+        Some(mint_info.key()),
+    )?;
+    token_interface::initialize_mint2(
+        CpiContext::new(
+            token_program.clone(),
+            token_interface::InitializeMint2 { mint: mint_info.clone() },
+        ),
+        0,
+        &voting_key,
+        None,
+    )?;
+    token_extensions::token_metadata_initialize(
+        CpiContext::new_with_signer(
+            token_program.clone(),
+            token_extensions::TokenMetadataInitialize {
+                program_id: token_program.clone(),
+                metadata: mint_info.clone(),
+                update_authority: voting_info.clone(),
+                mint: mint_info.clone(),
+                mint_authority: voting_info.clone(),
+            },
+            &[voting_seeds],
+        ),
+        name,
+        BADGE_TOKEN_SYMBOL.to_string(),
+        uri,
+    )?;
+
+    associated_token::create(CpiContext::new(
+        associated_token_program,
+        associated_token::Create {
+            payer: accounts.authority.to_account_info(),
+            associated_token: token_account.clone(),
+            authority: accounts.recipient_wallet.to_account_info(),
+            mint: mint_info.clone(),
+            system_program: accounts.system_program.to_account_info(),
+            token_program: token_program.clone(),
+        },
+    ))?;
+
+    token_interface::mint_to(
+        CpiContext::new_with_signer(
+            token_program.clone(),
+            token_interface::MintTo {
+                mint: mint_info.clone(),
+                to: token_account,
+                authority: voting_info.clone(),
+            },
+            &[voting_seeds],
+        ),
+        1,
+    )?;
+    token_interface::set_authority(
+        CpiContext::new_with_signer(
+            token_program,
+            token_interface::SetAuthority {
+                current_authority: voting_info,
+                account_or_mint: mint_info.clone(),
+            },
+            &[voting_seeds],
+        ),
+        AuthorityType::MintTokens,
+        None,
+    )?;
+
+    Ok(mint_info.key())
+}
+
+// Awards the badges `user` has earned under the configured thresholds. `candidates` are the
+// [b"badge", recipient, badge_type] PDAs the client expects to be earned: empty ones for an
+// earned badge are created and paid by `payer`, anything else is skipped, so passing a badge
+// twice or before it is earned is harmless.
+pub fn mint_earned_badges<'info>(
+    voting: &Voting,
+    user: &mut User,
+    stats: Option<&UserStats>,
+    candidates: &[AccountInfo<'info>],
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    moment: i64,
+) -> Result<()> {
+    let recipient = user.pubkey;
+    let earned = voting.badge_thresholds.earned(user, stats);
+
+    for candidate in candidates.iter().filter(|candidate| candidate.data_is_empty()) {
+        for &badge_type in earned.iter() {
+            let badge_seed = [badge_type as u8];
+            let (expected, bump) = Pubkey::find_program_address(
+                &[b"badge", recipient.as_ref(), &badge_seed],
+                &crate::ID,
+            );
+            if candidate.key() != expected {
+                continue;
+            }
+
+            create_pda_account(
+                system_program,
+                payer,
+                candidate,
+                &[b"badge", recipient.as_ref(), &badge_seed, &[bump]],
+                Rent::get()?.minimum_balance(UserBadge::MAX_SIZE),
+                UserBadge::MAX_SIZE as u64,
+                &crate::ID,
+            )?;
+
+            let user_badge = UserBadge {
+                badge_type,
+                data: String::new(),
+                recipient,
+                awarded_at: moment,
+                is_active: true,
+                bump,
+                mint: None,
+            };
+            user_badge.try_serialize(&mut &mut candidate.try_borrow_mut_data()?[..])?;
+            user.add_badge(badge_type)?;
+
+            emit!(BadgeAwarded {
+                recipient,
+                badge_type,
+                data: String::new(),
+                mint: None,
+                awarded_at: moment,
+            });
+            msg!("Badge {:?} earned by {}", badge_type, recipient);
+        }
+    }
+    user.reputation_score = user.calculate_reputation();
+    Ok(())
+}
+
+// Creates the PDA `to` the way Anchor's `init` does. `create_account` fails once anyone has sent
+// lamports to the address, so a pre-funded account is topped up, allocated and assigned instead.
+fn create_pda_account<'info>(
+    system_program: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    to: &AccountInfo<'info>,
+    seeds: &[&[u8]],
+    lamports: u64,
+    space: u64,
+    owner: &Pubkey,
+) -> Result<()> {
+    let current_lamports = to.lamports();
+    if current_lamports == 0 {
+        return system_program::create_account(
+            CpiContext::new_with_signer(
+                system_program.clone(),
+                system_program::CreateAccount {
+                    from: payer.clone(),
+                    to: to.clone(),
+                },
+                &[seeds],
+            ),
+            lamports,
+            space,
+            owner,
+        );
+    }
+
+    let top_up = lamports.saturating_sub(current_lamports);
+    if top_up > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                system_program.clone(),
+                system_program::Transfer {
+                    from: payer.clone(),
+                    to: to.clone(),
+                },
+            ),
+            top_up,
+        )?;
+    }
+    system_program::allocate(
+        CpiContext::new_with_signer(
+            system_program.clone(),
+            system_program::Allocate { account_to_allocate: to.clone() },
+            &[seeds],
+        ),
+        space,
+    )?;
+    system_program::assign(
+        CpiContext::new_with_signer(
+            system_program.clone(),
+            system_program::Assign { account_to_assign: to.clone() },
+            &[seeds],
+        ),
+        owner,
+    )
+}
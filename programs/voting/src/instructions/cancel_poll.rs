@@ -0,0 +1,33 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::error::*;
+
+#[derive(Accounts)]
+pub struct CancelPoll<'info> {
+    #[account(
+        mut,
+        constraint = poll.authority == authority.key() @ VotingError::UnauthorizedAccess,
+        constraint = poll.status.can_transition_to(PollStatus::Cancelled) @ VotingError::InvalidStatusTransition
+    )]
+    pub poll: Account<'info, Poll>,
+    #[account(seeds = [b"voting"], bump = voting.bump)]
+    pub voting: Account<'info, Voting>,
+
+    pub authority: Signer<'info>,
+}
+
+pub fn handler(ctx: Context<CancelPoll>, reason: String) -> Result<()> {
+    // a paused poll can still be cancelled, only the global pause stops it
+    ensure_operational(&ctx.accounts.voting, None)?;
+
+    let poll = &mut ctx.accounts.poll;
+    let moment = Clock::get()?.unix_timestamp;
+
+    require!(reason.len() <= 200, VotingError::DataTooLong);
+
+    let poll_key = poll.key();
+    poll.transition(poll_key, PollStatus::Cancelled, Some(reason), moment)?;
+
+    msg!("Poll cancelled: {}", poll.poll_name);
+    Ok(())
+}
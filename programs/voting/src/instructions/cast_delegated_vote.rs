@@ -0,0 +1,184 @@
+use anchor_lang::prelude::*;
+use crate::error::*;
+use crate::state::*;
+use crate::utils::*;
+
+#[event]
+pub struct DelegatedVoteCast {
+    pub poll: Pubkey,
+    pub delegatee: Pubkey,
+    pub nullifier_hash: [u8; 32],
+    pub delegators: Vec<Pubkey>,
+    pub ballot: Ballot,
+    pub vote_weight: u64,
+    pub timestamp: i64,
+}
+
+#[derive(Accounts)]
+#[instruction(ballot: Ballot, zk_proof: Vec<u8>, delegate_nullifier: [u8; 32])]
+pub struct CastDelegatedVote<'info> {
+    #[account(
+        mut,
+        constraint = poll.can_vote() @ VotingError::VotingNotAllowed
+    )]
+    pub poll: Account<'info, Poll>,
+    #[account(seeds = [b"voting"], bump = voting.bump)]
+    pub voting: Account<'info, Voting>,
+
+    #[account(
+        mut,
+        seeds = [b"poll_metadata", poll.key().as_ref()],
+        bump = poll_metadata.bump
+    )]
+    pub poll_metadata: Account<'info, PollMetadata>,
+
+    #[account(address = poll.verifying_key @ VotingError::InvalidVerifyingKey)]
+    pub verifying_key: Account<'info, VerifyingKey>,
+
+    #[account(
+        init,
+        payer = delegatee,
+        space = Vote::space(&poll),
+        seeds = [b"vote", poll.key().as_ref(), &delegate_nullifier],
+        bump
+    )]
+    pub vote: Account<'info, Vote>,
+
+    #[account(
+        init,
+        payer = delegatee,
+        space = VoterNullifier::MAX_SIZE,
+        seeds = [b"nullifier", poll.key().as_ref(), &delegate_nullifier],
+        bump
+    )]
+    pub voter_nullifier: Account<'info, VoterNullifier>,
+
+    // The voter's own delegation, if any, is withdrawn by voting directly
+    /// CHECK: deserialized only when initialized, the address is fixed by the seeds
+    #[account(mut, seeds = [b"delegation", poll.key().as_ref(), &delegate_nullifier], bump)]
+    pub own_delegation: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub delegatee: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// Casts the delegatee's own ballot together with the power of every `VoteDelegation` passed as
+// writable remaining accounts. Delegations may be transitive (A -> B -> C): each one must point
+// at the delegatee or at a delegator listed before it, and no chain may be longer than the
+// poll's `max_delegation_depth`. Each delegation adds one vote of weight and is consumed.
+// Standing delegations count here once the delegatee has applied them to the poll.
+pub fn handler<'info>(
+    ctx: Context<'_, '_, 'info, 'info, CastDelegatedVote<'info>>,
+    ballot: Ballot,
+    zk_proof: Vec<u8>,
+    delegate_nullifier: [u8; 32],
+    public_signals: Vec<[u8; 32]>,
+) -> Result<()> {
+    ensure_operational(&ctx.accounts.voting, Some(&ctx.accounts.poll))?;
+
+    let poll = &mut ctx.accounts.poll;
+    let vote = &mut ctx.accounts.vote;
+    let voter_nullifier = &mut ctx.accounts.voter_nullifier;
+    let poll_metadata = &mut ctx.accounts.poll_metadata;
+    let delegatee = ctx.accounts.delegatee.key();
+    let moment = Clock::get()?.unix_timestamp;
+
+    require!(poll.is_active(), VotingError::PollNotActive);
+    require!(moment <= poll.poll_end_time, VotingError::VotingEnded);
+    // encrypted ballots are 0/1 ciphertexts and cannot carry delegated weight
+    require!(!poll.encrypted_ballots, VotingError::OperationNotAllowed);
+
+    ballot.validate(poll)?;
+
+    // the delegatee proves their own eligibility; delegators proved theirs when delegating
+    require!(!zk_proof.is_empty(), VotingError::InvalidZkProof);
+    require!(
+        verify_zk_proof(&zk_proof, &public_signals, &ctx.accounts.verifying_key.groth16()?, &poll.key(), &delegate_nullifier)?,
+        VotingError::ZkProofVerificatiionFailed
+    );
+
+    require!(!ctx.remaining_accounts.is_empty(), VotingError::NoActiveDelegations);
+    require!(ctx.remaining_accounts.len() <= Vote::MAX_DELEGATORS, VotingError::TooManyDelegations);
+
+    // voting with delegated power is also a direct vote for the delegatee's own delegation
+    VoteDelegation::withdraw_for_direct_vote(&ctx.accounts.own_delegation)?;
+
+    // wallets whose power reaches this ballot, with their distance from the delegatee
+    let mut reached: Vec<(Pubkey, u8)> = vec![(delegatee, 0)];
+    let mut delegators = Vec::with_capacity(ctx.remaining_accounts.len());
+    for delegation_info in ctx.remaining_accounts.iter() {
+        require!(delegation_info.is_writable, VotingError::InvalidDelegationAccount);
+
+        let mut delegation: Account<'info, VoteDelegation> = Account::try_from(delegation_info)?;
+        require!(delegation.poll == poll.key(), VotingError::InvalidDelegationAccount);
+        let depth = reached
+            .iter()
+            .find(|(wallet, _)| *wallet == delegation.o_delegatee)
+            .map(|(_, depth)| depth + 1)
+            .ok_or(VotingError::InvalidDelegationAccount)?;
+        require!(depth <= poll.max_delegation_depth, VotingError::DelegationTooDeep);
+        require!(delegation.is_active, VotingError::DelegationNotActive);
+        require!(delegation.used_in.is_none(), VotingError::DelegationAlreadyUsed);
+        if let Some(expires_at) = delegation.expires_at {
+            require!(moment <= expires_at, VotingError::DelegationExpired);
+        }
+
+        delegation.used_in = Some(vote.key());
+        delegation.exit(&crate::ID)?;
+
+        reached.push((delegation.o_delegator, depth));
+        delegators.push(delegation.o_delegator);
+    }
+
+    // one vote for the delegatee plus one for every delegation consumed
+    let final_vote_weight = (delegators.len() as u64)
+        .checked_add(1)
+        .ok_or(VotingError::ArithmeticOverflow)?;
+
+    vote.poll = poll.key();
+    vote.nullifier_hash = delegate_nullifier;
+    vote.ballot = ballot.clone();
+    vote.zk_proof_hash = hash_zk_proof(&zk_proof);
+    vote.timestamp = moment;
+    vote.delegate_from = delegators.clone();
+    vote.delegate_to = false;
+    vote.vote_weight = final_vote_weight;
+    vote.tallied_round = if ballot.is_sealed() { 0 } else { 1 };
+    vote.bump = ctx.bumps.vote;
+    vote.locked_amount = 0;
+    vote.lock_owner = None;
+
+    voter_nullifier.poll = poll.key();
+    voter_nullifier.nullifier_hash = delegate_nullifier;
+    voter_nullifier.created_at = moment;
+    voter_nullifier.bump = ctx.bumps.voter_nullifier;
+    voter_nullifier.sequence = poll_metadata.record_nullifier(&delegate_nullifier)?;
+
+    poll.record_ballot(&ballot, final_vote_weight)?;
+
+    poll.unique_voters = poll.unique_voters
+        .checked_add(1)
+        .ok_or(VotingError::ArithmeticOverflow)?;
+
+    poll.updated_at = moment;
+
+    emit!(DelegatedVoteCast {
+        poll: poll.key(),
+        delegatee,
+        nullifier_hash: delegate_nullifier,
+        delegators,
+        ballot,
+        vote_weight: final_vote_weight,
+        timestamp: moment,
+    });
+
+    msg!(
+        "Delegated vote cast successfully: Poll {}, Weight {}",
+        poll.poll_name,
+        final_vote_weight
+    );
+
+    Ok(())
+}
@@ -1,26 +1,31 @@
 use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Token, TokenAccount};
 use crate::error::*;
 use crate::state::*;
 use crate::utils::*;
+use crate::instructions::award_badge::mint_earned_badges;
 
 #[event]
 pub struct VoteCast {
     pub poll: Pubkey,
     pub timestamp: i64,
     pub nullifier_hash: [u8; 32],
-    pub vote_choice: u8,
+    pub ballot: Ballot,
     pub vote_weight: u64,
     pub total_votes: u64,
+    pub nullifier_sequence: u64,
 }
 
 #[derive(Accounts)]
-#[instruction(nullifier_hash: [u8; 32])]
+#[instruction(ballot: Ballot, zk_proof: Vec<u8>, nullifier_hash: [u8; 32])]
 pub struct CastVote<'info> {
     #[account(
         mut,
         constraint = poll.can_vote() @ VotingError::VotingNotAllowed
     )]
     pub poll: Account<'info, Poll>,
+    #[account(seeds = [b"voting"], bump = voting.bump)]
+    pub voting: Account<'info, Voting>,
     
     #[account(
         mut,
@@ -28,11 +33,15 @@ pub struct CastVote<'info> {
         bump = poll_metadata.bump
     )]
     pub poll_metadata: Account<'info, PollMetadata>,
+
+    // Left out on allowlist polls, which take a membership proof instead
+    #[account(address = poll.verifying_key @ VotingError::InvalidVerifyingKey)]
+    pub verifying_key: Option<Account<'info, VerifyingKey>>,
     
     #[account(
         init,
         payer = voter,
-        space = Vote::MAX_SIZE,
+        space = Vote::space(&poll),
         seeds = [b"vote", poll.key().as_ref(), &nullifier_hash],
         bump
     )]
@@ -46,21 +55,58 @@ pub struct CastVote<'info> {
         bump
     )]
     pub voter_nullifier: Account<'info, VoterNullifier>,
+
+    // The voter's own delegation, if any, is withdrawn by voting directly
+    /// CHECK: deserialized only when initialized, the address is fixed by the seeds
+    #[account(mut, seeds = [b"delegation", poll.key().as_ref(), &nullifier_hash], bump)]
+    pub own_delegation: UncheckedAccount<'info>,
+
+    // Required on token-weighted polls: marks the voter's snapshot balance as used
+    #[account(
+        init,
+        payer = voter,
+        space = WeightClaim::MAX_SIZE,
+        seeds = [b"weight_claim", poll.key().as_ref(), voter.key().as_ref()],
+        bump
+    )]
+    pub weight_claim: Option<Account<'info, WeightClaim>>,
+
+    // Required on escrow polls: the poll's vault and the voter's account the lock is paid from
+    #[account(mut, seeds = [b"escrow_vault", poll.key().as_ref()], bump)]
+    pub escrow_vault: Option<Account<'info, TokenAccount>>,
+    #[account(mut, constraint = voter_token_account.owner == voter.key() @ VotingError::InvalidEscrowAccount)]
+    pub voter_token_account: Option<Account<'info, TokenAccount>>,
+    pub token_program: Option<Program<'info, Token>>,
     
+    // Optional profile linkage. Only counters and the poll are recorded, never the ballot or the
+    // nullifier; voters who relay through another fee payer for anonymity should leave these out.
+    #[account(mut, seeds = [b"user", voter.key().as_ref()], bump = user.bump)]
+    pub user: Option<Account<'info, User>>,
+    #[account(mut, seeds = [b"user_stats", voter.key().as_ref()], bump = user_stats.bump)]
+    pub user_stats: Option<Account<'info, UserStats>>,
+
     #[account(mut)]
     pub voter: Signer<'info>,
+    // Allowlist key that signs for the member when it is not the fee payer
+    pub member: Option<Signer<'info>>,
     
     pub system_program: Program<'info, System>,
 }
 
-pub fn handler(
-    ctx: Context<CastVote>,
-    vote_choice: u8,
+// On token-gated polls the accounts for `TokenGate::verify` come first in the remaining accounts.
+// Badge PDAs the voter expects to earn may follow, see `mint_earned_badges`; they are only
+// minted when `user` is given.
+pub fn handler<'info>(
+    ctx: Context<'_, '_, '_, 'info, CastVote<'info>>,
+    ballot: Ballot,
     zk_proof: Vec<u8>,
     nullifier_hash: [u8; 32],
-    public_signals: Vec<String>,
-    vote_weight: Option<u64>,
+    public_signals: Vec<[u8; 32]>,
+    extras: VoteExtras,
 ) -> Result<()> {
+    let VoteExtras { weight_proof, lock_amount, membership_proof } = extras;
+    ensure_operational(&ctx.accounts.voting, Some(&ctx.accounts.poll))?;
+
     let poll = &mut ctx.accounts.poll;
     let vote = &mut ctx.accounts.vote;
     let voter_nullifier = &mut ctx.accounts.voter_nullifier;
@@ -72,60 +118,125 @@ pub fn handler(
     require!(moment <= poll.poll_end_time, VotingError::VotingEnded);
     require!(poll.can_vote(), VotingError::VotingNotAllowed);
 
-    // Validate vote choice
-    require!(
-        (vote_choice as usize) < poll.options.len(),
-        VotingError::InvalidVoteChoice
-    );
+    // Validate the ballot against the poll type and options
+    ballot.validate(poll)?;
 
-    // Verify ZK proof
-    require!(!zk_proof.is_empty(), VotingError::InvalidZkProof);
-    require!(
-        verify_zk_proof(&zk_proof, &public_signals, &poll.eligibility_criteria)?,
-        VotingError::ZkProofVerificatiionFailed
-    );
+    // Verify eligibility: a Merkle proof on allowlist polls, a ZK proof otherwise
+    match poll.allowlist_root {
+        Some(ref root) => {
+            require!(zk_proof.is_empty(), VotingError::InvalidZkProof);
+            let membership_proof = membership_proof.as_ref().ok_or(VotingError::InvalidMembershipProof)?;
+            let member = ctx.accounts.member.as_ref().unwrap_or(&ctx.accounts.voter).key();
+            membership_proof.verify(root, &poll.key(), &member, &nullifier_hash)?;
+        },
+        None => {
+            require!(membership_proof.is_none(), VotingError::InvalidMembershipProof);
+            let verifying_key = ctx.accounts.verifying_key.as_ref().ok_or(VotingError::InvalidVerifyingKey)?;
+            require!(!zk_proof.is_empty(), VotingError::InvalidZkProof);
+            require!(
+                verify_zk_proof(&zk_proof, &public_signals, &verifying_key.groth16()?, &poll.key(), &nullifier_hash)?,
+                VotingError::ZkProofVerificatiionFailed
+            );
+        },
+    }
 
-    // Calculate vote weight
-    let final_vote_weight = match poll.poll_type {
-        PollType::Quadratic => {
-            let weight = vote_weight.unwrap_or(1);
-            require!(weight > 0, VotingError::InvalidVoteWeight);
-            
-            if let Some(ref params) = poll.quadratic_parameters {
-                require!(
-                    weight <= params.max_credits,
-                    VotingError::InsufficientCredits
-                );
-                // Calculate quadratic cost: weight^2
-                weight * weight
-            } else {
-                weight
-            }
+    let badge_candidates = match poll.token_gate {
+        Some(ref gate) => {
+            gate.verify(&ctx.accounts.voter.key(), ctx.remaining_accounts)?;
+            &ctx.remaining_accounts[gate.account_count()..]
         },
-        _ => vote_weight.unwrap_or(1),
+        None => ctx.remaining_accounts,
     };
 
+    VoteDelegation::withdraw_for_direct_vote(&ctx.accounts.own_delegation)?;
+
+    // Vote weight is never taken from the caller: one per ballot, or the snapshot balance the
+    // voter's wallet proves on token-weighted polls. Quadratic votes and their credit cost are
+    // carried by the ballot's allocations.
+    let final_vote_weight = match poll.token_weight {
+        Some(ref token_weight) => {
+            let weight_proof = weight_proof.as_ref().ok_or(VotingError::InvalidWeightProof)?;
+            let weight_claim = ctx.accounts.weight_claim.as_mut().ok_or(VotingError::InvalidWeightProof)?;
+            let weight = token_weight.verify(&ctx.accounts.voter.key(), weight_proof)?;
+
+            weight_claim.poll = poll.key();
+            weight_claim.owner = ctx.accounts.voter.key();
+            weight_claim.balance = weight;
+            weight_claim.vote = vote.key();
+            weight_claim.bump = ctx.bumps.weight_claim.unwrap();
+            weight
+        },
+        None => {
+            require!(weight_proof.is_none(), VotingError::InvalidWeightProof);
+            1
+        },
+    };
+
+    // Escrow polls lock tokens for the life of the poll, the voter's wallet is recorded for the refund
+    let locked_amount = match poll.token_escrow {
+        Some(ref escrow) => {
+            let amount = escrow.validate_lock(lock_amount)?;
+
+            let (escrow_vault, voter_token_account, token_program) = match (
+                ctx.accounts.escrow_vault.as_ref(),
+                ctx.accounts.voter_token_account.as_ref(),
+                ctx.accounts.token_program.as_ref(),
+            ) {
+                (Some(vault), Some(source), Some(token_program)) => (vault, source, token_program),
+                _ => return err!(VotingError::InvalidEscrowAccount),
+            };
+            require!(voter_token_account.mint == escrow.mint, VotingError::InvalidEscrowAccount);
+
+            token::transfer(
+                CpiContext::new(
+                    token_program.to_account_info(),
+                    token::Transfer {
+                        from: voter_token_account.to_account_info(),
+                        to: escrow_vault.to_account_info(),
+                        authority: ctx.accounts.voter.to_account_info(),
+                    },
+                ),
+                amount,
+            )?;
+            amount
+        },
+        None => {
+            require!(lock_amount.is_none(), VotingError::InvalidLockAmount);
+            0
+        },
+    };
+    let final_vote_weight = match poll.token_escrow {
+        Some(ref escrow) => escrow.ballot_weight(locked_amount, final_vote_weight),
+        None => final_vote_weight,
+    };
+
+    // ciphertexts encrypt 0 or 1, so encrypted ballots cannot carry extra weight
+    if poll.encrypted_ballots {
+        require!(final_vote_weight == 1, VotingError::InvalidVoteWeight);
+    }
+
     // Initialize vote record
     vote.poll = poll.key();
     vote.nullifier_hash = nullifier_hash;
-    vote.vote_choice = vote_choice;
+    vote.ballot = ballot.clone();
     vote.zk_proof_hash = hash_zk_proof(&zk_proof);
     vote.timestamp = moment;
-    vote.delegate_from = None;
+    vote.delegate_from = Vec::new();
     vote.delegate_to = false;
     vote.vote_weight = final_vote_weight;
+    vote.tallied_round = if ballot.is_sealed() { 0 } else { 1 };
     vote.bump = ctx.bumps.vote;
+    vote.lock_tokens(locked_amount, ctx.accounts.voter.key());
 
     // Initialize voter nullifier
     voter_nullifier.poll = poll.key();
     voter_nullifier.nullifier_hash = nullifier_hash;
     voter_nullifier.created_at = moment;
     voter_nullifier.bump = ctx.bumps.voter_nullifier;
+    voter_nullifier.sequence = poll_metadata.record_nullifier(&nullifier_hash)?;
 
-    // Update poll statistics
-    poll.total_votes = poll.total_votes
-        .checked_add(final_vote_weight)
-        .ok_or(VotingError::ArithmeticOverflow)?;
+    // Update poll statistics and vote counts
+    poll.record_ballot(&ballot, final_vote_weight)?;
     
     poll.unique_voters = poll.unique_voters
         .checked_add(1)
@@ -133,37 +244,36 @@ pub fn handler(
     
     poll.updated_at = moment;
 
-    // Update vote counts array
-    if poll.vote_counts.len() <= vote_choice as usize {
-        poll.vote_counts.resize(poll.options.len(), 0);
+    if let Some(user_stats) = ctx.accounts.user_stats.as_mut() {
+        user_stats.record_participation(poll.key(), moment);
+    }
+    if let Some(user) = ctx.accounts.user.as_mut() {
+        user.record_vote()?;
+        mint_earned_badges(
+            &ctx.accounts.voting,
+            user,
+            ctx.accounts.user_stats.as_deref(),
+            badge_candidates,
+            &ctx.accounts.voter.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            moment,
+        )?;
     }
-    
-    poll.vote_counts[vote_choice as usize] = poll.vote_counts[vote_choice as usize]
-        .checked_add(final_vote_weight)
-        .ok_or(VotingError::ArithmeticOverflow)?;
-
-    // Add nullifier to poll's nullifier list
-    poll.nullifier_hashes.push(nullifier_hash);
-    
-    // Update poll metadata
-    poll_metadata.nullifier_count = poll_metadata.nullifier_count
-        .checked_add(1)
-        .ok_or(VotingError::ArithmeticOverflow)?;
 
     // Emit vote cast event
     emit!(VoteCast {
         poll: poll.key(),
         timestamp: moment,
         nullifier_hash,
-        vote_choice,
+        ballot,
         vote_weight: final_vote_weight,
         total_votes: poll.total_votes,
+        nullifier_sequence: voter_nullifier.sequence,
     });
 
     msg!(
-        "Vote cast successfully: Poll {}, Choice {}, Weight {}",
+        "Vote cast successfully: Poll {}, Weight {}",
         poll.poll_name,
-        vote_choice,
         final_vote_weight
     );
 
@@ -1,50 +1,141 @@
 use anchor_lang::prelude::*;
 use crate::state::*;
 use crate::error::*;
+use crate::instructions::tally_ranked_choice::RankedChoiceRoundCompleted;
 
 #[event]
 pub struct PollClosed {
     pub poll: Pubkey,
     pub authority: Pubkey,
     pub total_votes: u64,
-    pub top_index: u8,
+    pub winners: Vec<u8>,
     pub winner_votes: u64,
+    pub is_final: bool,
     pub unique_voters: u64,
+    pub unrevealed_votes: u64,
+    pub nullifier_count: u64,
+    pub nullifier_accumulator: [u8; 32],
     pub ended_at: i64,
 }
 
 #[derive(Accounts)]
 pub struct ClosePoll<'info> {
-    #[account(mut, constraint = poll.authority == authority.key() @ VotingError::UnauthorizedAccess, constraint = poll.status == PollStatus::Active || poll.status == PollStatus::Pending @ VotingError::PollAlreadyClosed)]
+    #[account(mut, constraint = poll.authority == authority.key() @ VotingError::UnauthorizedAccess, constraint = poll.status.can_transition_to(PollStatus::Closed) @ VotingError::InvalidStatusTransition)]
     pub poll: Account<'info, Poll>,
+    #[account(seeds = [b"voting"], bump = voting.bump)]
+    pub voting: Account<'info, Voting>,
     #[account(seeds = [b"poll_metadata", poll.key().as_ref()], bump = poll_metadata.bump)]
     pub poll_metadata: Account<'info, PollMetadata>,
+
+    #[account(init, payer = authority, space = PollResult::MAX_SIZE, seeds = [b"poll_result", poll.key().as_ref()], bump)]
+    pub poll_result: Account<'info, PollResult>,
+
+    // Required for ranked-choice polls, where the instant-runoff count starts at close
+    #[account(init, payer = authority, space = RankedChoiceTally::MAX_SIZE, seeds = [b"ranked_tally", poll.key().as_ref()], bump)]
+    pub ranked_choice_tally: Option<Account<'info, RankedChoiceTally>>,
+
+    #[account(mut)]
     pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
 }
 
 pub fn handler( ctx: Context<ClosePoll>) -> Result<()> {
+    ensure_operational(&ctx.accounts.voting, Some(&ctx.accounts.poll))?;
+
     let poll = &mut ctx.accounts.poll;
+    let poll_result = &mut ctx.accounts.poll_result;
+    let poll_metadata = &ctx.accounts.poll_metadata;
     let moment = Clock::get()?.unix_timestamp;
 
-    let closure = moment >= poll.poll_end_time || poll.authority == ctx.accounts.authority.key();
-    require!(closure, VotingError::CannotClosePoll);
+    // ending a poll early is a cancellation, see `cancel_poll`
+    require!(moment >= poll.poll_end_time, VotingError::CannotClosePoll);
+
+    // a commit-reveal poll cannot be closed while ballots may still be revealed
+    if poll.reveal_window.is_some() {
+        require!(moment > poll.counting_end_time(), VotingError::CannotClosePoll);
+    }
+
+    let poll_key = poll.key();
+    poll.transition(poll_key, PollStatus::Closed, None, moment)?;
+
+    poll_result.poll = poll_key;
+    poll_result.tally_method = PollResult::tally_method(poll);
+    poll_result.vote_counts = poll.vote_counts.clone();
+    poll_result.total_votes = poll.total_votes;
+    poll_result.unique_voters = poll.unique_voters;
+    poll_result.counted_ballots = poll.counted_ballots();
+    poll_result.max_voters = poll.max_voters;
+    poll_result.winners = Vec::new();
+    poll_result.is_tie = false;
+    poll_result.is_final = false;
+    poll_result.nullifier_count = poll_metadata.nullifier_count;
+    poll_result.voter_set_hash = poll_metadata.nullifier_accumulator;
+    poll_result.closed_at = moment;
+    poll_result.finalized_at = None;
+    poll_result.bump = ctx.bumps.poll_result;
+
+    if poll.poll_type == PollType::RankedChoice {
+        let tally = ctx.accounts.ranked_choice_tally.as_mut().ok_or(VotingError::MissingTallyAccount)?;
+
+        // first preferences were counted as ballots came in, so round 1 is already known
+        tally.poll = poll.key();
+        tally.round = 1;
+        tally.eliminated = 0;
+        tally.elimination_order = Vec::new();
+        tally.round_counts = poll.vote_counts.clone();
+        tally.exhausted_weight = 0;
+        tally.ballots_counted = poll.counted_ballots();
+        tally.total_ballots = poll.counted_ballots();
+        tally.first_round_counts = poll.vote_counts.clone();
+        tally.winner = None;
+        tally.is_complete = false;
+        tally.completed_at = None;
+        tally.bump = ctx.bumps.ranked_choice_tally.unwrap();
+        tally.finish_round(moment);
+
+        emit!(RankedChoiceRoundCompleted {
+            poll: poll.key(),
+            round: 1,
+            round_counts: poll.vote_counts.clone(),
+            exhausted_weight: 0,
+            eliminated: tally.elimination_order.last().copied(),
+            winner: tally.winner,
+        });
 
-    poll.status = PollStatus::Closed;
-    poll.updated_at = moment;
+        // decided by a first-round majority, otherwise `tally_ranked_choice` finalizes the result
+        if tally.is_complete {
+            poll_result.finalize(tally.round_counts.clone(), tally.winner.into_iter().collect(), moment);
+        }
+    } else if poll_result.tally_method != TallyMethod::Encrypted {
+        // encrypted totals are only known once `publish_tally` decrypts them
+        poll_result.finalize(poll.vote_counts.clone(), PollResult::leaders(&poll.vote_counts), moment);
+    }
 
-    // polll winnner determination for multi-choice poll
-    let winner_position =  poll.vote_counts.iter().enumerate().max_by_key(|(_, &count) | count).map(| (position, _)| position).unwrap_or(0);
+    let winner_votes = poll_result.winners
+        .first()
+        .map(|&option| poll_result.vote_counts[option as usize])
+        .unwrap_or(0);
 
     emit!(PollClosed {
         poll: poll.key(),
         authority: poll.authority,
         total_votes: poll.total_votes,
-        top_index: winner_position as u8,
-        winner_votes: poll.vote_counts[winner_position],
+        winners: poll_result.winners.clone(),
+        winner_votes,
+        is_final: poll_result.is_final,
         unique_voters: poll.unique_voters,
+        unrevealed_votes: poll.unique_voters
+            .checked_sub(poll.counted_ballots())
+            .ok_or(VotingError::ArithmeticOverflow)?,
+        nullifier_count: poll_metadata.nullifier_count,
+        nullifier_accumulator: poll_metadata.nullifier_accumulator,
         ended_at: moment,
     });
-    msg!("Poll closed: {} - Winner: {}", poll.name, poll.options[winner_position]);
+    match poll_result.winners.as_slice() {
+        [winner] => msg!("Poll closed: {} - Winner: {}", poll.poll_name, poll.options[*winner as usize]),
+        [] => msg!("Poll closed: {} - No winner decided", poll.poll_name),
+        tied => msg!("Poll closed: {} - Tie between {} options", poll.poll_name, tied.len()),
+    }
 
     Ok(())
 }
\ No newline at end of file
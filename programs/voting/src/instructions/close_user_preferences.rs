@@ -0,0 +1,24 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+
+#[derive(Accounts)]
+pub struct CloseUserPreferences<'info> {
+    #[account(mut, close = owner, seeds = [b"user_preferences", owner.key().as_ref()], bump = user_preferences.bump)]
+    pub user_preferences: Account<'info, UserPreferences>,
+    #[account(mut, seeds = [b"user_stats", owner.key().as_ref()], bump = user_stats.bump)]
+    pub user_stats: Account<'info, UserStats>,
+    #[account(seeds = [b"voting"], bump = voting.bump)]
+    pub voting: Account<'info, Voting>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+}
+
+// Back to the defaults; history already hidden stays cleared
+pub fn handler(ctx: Context<CloseUserPreferences>) -> Result<()> {
+    ensure_operational(&ctx.accounts.voting, None)?;
+
+    ctx.accounts.user_stats.set_hide_voting_history(false);
+
+    msg!("User preferences closed for {}", ctx.accounts.owner.key());
+    Ok(())
+}
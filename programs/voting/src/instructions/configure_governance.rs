@@ -0,0 +1,49 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::error::*;
+
+#[event]
+pub struct GovernanceConfigured {
+    pub admins: Vec<Pubkey>,
+    pub threshold: u8,
+    pub timelock: i64,
+    pub governance_version: u32,
+    pub configured_at: i64,
+}
+
+#[derive(Accounts)]
+pub struct ConfigureGovernance<'info> {
+    #[account(mut, seeds = [b"voting"], bump = voting.bump)]
+    pub voting: Account<'info, Voting>,
+    pub authority: Signer<'info>,
+}
+
+// Hands the sensitive actions of the single authority over to an M-of-N admin set. This can only
+// be done once; later changes go through an `UpdateGovernance` proposal.
+pub fn handler(ctx: Context<ConfigureGovernance>, admins: Vec<Pubkey>, threshold: u8, timelock: i64) -> Result<()> {
+    ensure_operational(&ctx.accounts.voting, None)?;
+
+    let voting = &mut ctx.accounts.voting;
+    let moment = Clock::get()?.unix_timestamp;
+
+    voting.ensure_single_authority(&ctx.accounts.authority.key())?;
+    require!(validate_admin_set(&admins, threshold, timelock), VotingError::InvalidAdminSet);
+
+    voting.admins = admins.clone();
+    voting.threshold = threshold;
+    voting.timelock = timelock;
+    voting.governance_version = voting.governance_version
+        .checked_add(1)
+        .ok_or(VotingError::ArithmeticOverflow)?;
+
+    emit!(GovernanceConfigured {
+        admins,
+        threshold,
+        timelock,
+        governance_version: voting.governance_version,
+        configured_at: moment,
+    });
+
+    msg!("Governance configured: {}-of-{}", threshold, voting.admins.len());
+    Ok(())
+}
@@ -1,6 +1,10 @@
 use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+use anchor_spl::token::{Mint, Token, TokenAccount};
 use crate::state::*;
 use crate::error::*;
+use crate::utils::*;
+use crate::instructions::award_badge::mint_earned_badges;
 
 #[event]
 pub struct PollCreated {
@@ -8,9 +12,13 @@ pub struct PollCreated {
     pub authority: Pubkey,
     pub poll_name: String,
     pub poll_type: PollType,
+    pub verifying_key: Pubkey,
     pub start_time: i64,
     pub end_time: i64,
     pub max_voters: Option<u64>,
+    pub tags: Vec<String>,
+    pub token_gate: Option<TokenGate>,
+    pub allowlist_root: Option<[u8; 32]>,
     pub created_at: i64,
 }
 
@@ -25,6 +33,8 @@ pub struct CreatePoll<'info> {
         bump
     )]
     pub poll: Account<'info, Poll>,
+    #[account(mut, seeds = [b"voting"], bump = voting.bump)]
+    pub voting: Account<'info, Voting>,
     
     #[account(
         init,
@@ -34,15 +44,42 @@ pub struct CreatePoll<'info> {
         bump
     )]
     pub poll_metadata: Account<'info, PollMetadata>,
+
+    // Circuit that every ballot on this poll must be proven against, unless it uses an allowlist
+    #[account(constraint = verifying_key.is_finalized @ VotingError::VerifyingKeyNotFinalized)]
+    pub verifying_key: Option<Account<'info, VerifyingKey>>,
     
+    // Required when the poll escrows tokens: the vault ballots lock into and its signer
+    pub escrow_mint: Option<Account<'info, Mint>>,
+    #[account(
+        init,
+        payer = authority,
+        token::mint = escrow_mint,
+        token::authority = escrow_authority,
+        seeds = [b"escrow_vault", poll.key().as_ref()],
+        bump
+    )]
+    pub escrow_vault: Option<Account<'info, TokenAccount>>,
+    /// CHECK: PDA that only signs for the vault
+    #[account(seeds = [b"escrow_authority", poll.key().as_ref()], bump)]
+    pub escrow_authority: Option<UncheckedAccount<'info>>,
+    pub token_program: Option<Program<'info, Token>>,
+
+    // Optional: credits the poll to the creator's profile
+    #[account(mut, seeds = [b"user", authority.key().as_ref()], bump = user.bump)]
+    pub user: Option<Account<'info, User>>,
+
     #[account(mut)]
     pub authority: Signer<'info>,
     
     pub system_program: Program<'info, System>,
 }
 
-pub fn handler(
-    ctx: Context<CreatePoll>,
+// Badge PDAs the creator expects to earn may be passed as remaining accounts, see
+// `mint_earned_badges`; they are only minted when `user` is given.
+#[allow(clippy::too_many_arguments)]
+pub fn handler<'info>(
+    ctx: Context<'_, '_, '_, 'info, CreatePoll<'info>>,
     poll_name: String,
     options: Vec<String>,
     description: String,
@@ -55,7 +92,31 @@ pub fn handler(
     quadratic_parameters: Option<QuadraticParameters>,
     allow_recast: bool,
     recast_vote_window: Option<i64>,
+    approval_parameters: Option<ApprovalParameters>,
+    reveal_window: Option<i64>,
+    encrypted_ballots: bool,
+    max_delegation_depth: u8,
+    tags: Vec<String>,
+    access: PollAccessConfig,
 ) -> Result<()> {
+    let PollAccessConfig { token_gate, token_weight, token_escrow, allowlist_root } = access;
+    ensure_operational(&ctx.accounts.voting, None)?;
+
+    // creation fee set by governance, paid into the config account
+    let fee = ctx.accounts.voting.poll_creation_fee;
+    if fee > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.authority.to_account_info(),
+                    to: ctx.accounts.voting.to_account_info(),
+                },
+            ),
+            fee,
+        )?;
+    }
+
     let poll = &mut ctx.accounts.poll;
     let poll_metadata = &mut ctx.accounts.poll_metadata;
     let current_time = Clock::get()?.unix_timestamp;
@@ -85,17 +146,86 @@ pub fn handler(
 
     // Validate quadratic parameters if provided
     if let Some(ref quad_params) = quadratic_parameters {
-        require!(poll_type == PollType::Quadratic, VotingError::InvalidPollDuration);
+        require!(poll_type == PollType::Quadratic, VotingError::InvalidBallotType);
         require!(quad_params.max_credits > 0, VotingError::InvalidVoteWeight);
-        require!(!quad_params.credit_cost_curve.is_empty(), VotingError::InvalidVoteWeight);
+        validate_cost_curve(&quad_params.credit_cost_curve)?;
+    } else {
+        require!(poll_type != PollType::Quadratic, VotingError::InvalidVoteWeight);
+    }
+
+    // Validate reveal window if the poll uses commit-reveal
+    if let Some(window) = reveal_window {
+        require!(window > 0 && window <= poll_duration, VotingError::InvalidPollDuration);
+    }
+
+    // Encrypted ballots are one-of-N ciphertexts, aggregated once and never replaced.
+    // The trustees' keys are registered with `setup_encrypted_tally` before voting starts.
+    if encrypted_ballots {
+        require!(
+            poll_type == PollType::YesNo || poll_type == PollType::AnonymousFeedback,
+            VotingError::InvalidBallotType
+        );
+        require!(options.len() <= MAX_ENCRYPTED_OPTIONS, VotingError::TooManyOptions);
+        require!(reveal_window.is_none() && !allow_recast, VotingError::OperationNotAllowed);
+        require!(poll_begin_time > current_time, VotingError::PollStartTimeInPast);
+    }
+
+    require!(max_delegation_depth <= MAX_DELEGATION_DEPTH, VotingError::DelegationTooDeep);
+    require!(validate_tags(&tags), VotingError::InvalidTags);
+
+    // Token-weighted polls take their weights from a balance snapshot, see `TokenWeight`
+    require!(
+        (poll_type == PollType::TokenWeighted) == token_weight.is_some(),
+        VotingError::InvalidVoteWeight
+    );
+
+    if let Some(ref escrow) = token_escrow {
+        let escrow_mint = ctx.accounts.escrow_mint.as_ref().ok_or(VotingError::InvalidEscrowAccount)?;
+        require!(escrow_mint.key() == escrow.mint, VotingError::InvalidEscrowAccount);
+        require!(ctx.accounts.escrow_vault.is_some(), VotingError::InvalidEscrowAccount);
+        // a lock-weighted ballot cannot also be weighted by a snapshot, credits or be encrypted
+        if escrow.weight_by_lock {
+            require!(
+                token_weight.is_none() && poll_type != PollType::Quadratic && !encrypted_ballots,
+                VotingError::InvalidVoteWeight
+            );
+        }
+    }
+
+    // Allowlist polls prove membership with a Merkle proof instead of a circuit. Recasts and
+    // delegations still go through ZK proofs, so they are not available there.
+    if allowlist_root.is_some() {
+        require!(ctx.accounts.verifying_key.is_none(), VotingError::InvalidVerifyingKey);
+        require!(!allow_recast, VotingError::RecastNotAllowed);
+    } else {
+        require!(ctx.accounts.verifying_key.is_some(), VotingError::InvalidVerifyingKey);
+    }
+
+    // Delegated power is never checked against the gate, the snapshot or the escrow, so such
+    // polls cannot take delegations
+    if token_gate.is_some() || token_weight.is_some() || token_escrow.is_some() || allowlist_root.is_some() {
+        require!(max_delegation_depth == 0, VotingError::OperationNotAllowed);
+    }
+
+    // Validate approval selection limits if provided
+    if let Some(ref approval_params) = approval_parameters {
+        require!(poll_type == PollType::MultiChoice, VotingError::InvalidBallotType);
+        require!(
+            approval_params.min_selections >= 1
+                && approval_params.min_selections <= approval_params.max_selections
+                && approval_params.max_selections as usize <= options.len(),
+            VotingError::InvalidSelectionCount
+        );
     }
 
     // Initialize poll
     poll.authority = ctx.accounts.authority.key();
+    poll.pending_authority = None;
     poll.poll_name = poll_name.clone();
     poll.options = options.clone();
     poll.description = description;
     poll.eligibility_criteria = eligibility_criteria;
+    poll.verifying_key = ctx.accounts.verifying_key.as_ref().map_or(Pubkey::default(), |verifying_key| verifying_key.key());
     poll.status = if poll_begin_time <= current_time {
         PollStatus::Active
     } else {
@@ -106,7 +236,6 @@ pub fn handler(
     poll.poll_end_time = poll_end_time;
     poll.max_voters = max_voters;
     poll.total_votes = 0;
-    poll.nullifier_hashes = Vec::new();
     poll.vote_counts = vec![0; options.len()];
     poll.recast_vote_window = recast_vote_window;
     poll.unique_voters = 0;
@@ -115,24 +244,60 @@ pub fn handler(
     poll.allow_recast = allow_recast;
     poll.geolocation_required = geolocation_required;
     poll.quadratic_parameters = quadratic_parameters;
+    poll.approval_parameters = approval_parameters;
+    poll.reveal_window = reveal_window;
+    poll.revealed_votes = 0;
+    poll.encrypted_ballots = encrypted_ballots;
+    poll.max_delegation_depth = max_delegation_depth;
+    poll.tags = tags;
+    poll.token_gate = token_gate;
+    poll.token_weight = token_weight;
+    poll.token_escrow = token_escrow;
+    poll.allowlist_root = allowlist_root;
+    poll.is_paused = false;
     poll.bump = ctx.bumps.poll;
 
     // Initialize poll metadata
     poll_metadata.poll = poll.key();
     poll_metadata.nullifier_count = 0;
+    poll_metadata.nullifier_accumulator = [0u8; 32];
     poll_metadata.bump = ctx.bumps.poll_metadata;
 
+    if let Some(user) = ctx.accounts.user.as_mut() {
+        user.record_poll_created()?;
+        mint_earned_badges(
+            &ctx.accounts.voting,
+            user,
+            None,
+            ctx.remaining_accounts,
+            &ctx.accounts.authority.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            current_time,
+        )?;
+    }
+
     // Emit poll created event
     emit!(PollCreated {
         poll: poll.key(),
         authority: ctx.accounts.authority.key(),
         poll_name: poll_name.clone(),
         poll_type: poll_type.clone(),
+        verifying_key: poll.verifying_key,
         start_time: poll_begin_time,
         end_time: poll_end_time,
         max_voters,
+        tags: poll.tags.clone(),
+        token_gate: poll.token_gate.clone(),
+        allowlist_root: poll.allowlist_root,
         created_at: current_time,
     });
+    emit!(PollStatusChanged {
+        poll: poll.key(),
+        from: None,
+        to: poll.status,
+        reason: None,
+        changed_at: current_time,
+    });
 
     msg!(
         "Poll '{}' created successfully by {} with {} options",
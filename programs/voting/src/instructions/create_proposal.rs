@@ -0,0 +1,86 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::error::*;
+
+#[event]
+pub struct ProposalCreated {
+    pub proposal: Pubkey,
+    pub id: u64,
+    pub proposer: Pubkey,
+    pub action: GovernanceAction,
+    pub created_at: i64,
+}
+
+#[derive(Accounts)]
+pub struct CreateProposal<'info> {
+    #[account(mut, seeds = [b"voting"], bump = voting.bump)]
+    pub voting: Account<'info, Voting>,
+    #[account(
+        init,
+        payer = proposer,
+        space = GovernanceProposal::MAX_SIZE,
+        seeds = [b"proposal", &voting.proposal_count.to_le_bytes()],
+        bump
+    )]
+    pub proposal: Account<'info, GovernanceProposal>,
+    #[account(mut)]
+    pub proposer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+// Any admin may propose; the proposal counts as the proposer's approval
+pub fn handler(ctx: Context<CreateProposal>, action: GovernanceAction) -> Result<()> {
+    // no `ensure_operational`: governance stays usable while paused so a `Resume` can pass
+    let voting = &mut ctx.accounts.voting;
+    let proposal = &mut ctx.accounts.proposal;
+    let proposer = ctx.accounts.proposer.key();
+    let moment = Clock::get()?.unix_timestamp;
+
+    require!(voting.has_governance(), VotingError::OperationNotAllowed);
+
+    match action {
+        GovernanceAction::Pause { ref reason } | GovernanceAction::Resume { ref reason } => {
+            require!(reason.len() <= MAX_GOVERNANCE_REASON, VotingError::DataTooLong);
+        },
+        GovernanceAction::AwardBadge { ref data, ref token_uri, .. } => {
+            require!(data.len() <= 200, VotingError::DataTooLong);
+            require!(
+                token_uri.as_ref().is_none_or(|uri| uri.len() <= MAX_BADGE_URI_LENGTH),
+                VotingError::DataTooLong
+            );
+        },
+        GovernanceAction::UpdateGovernance { ref admins, threshold, timelock } => {
+            require!(validate_admin_set(admins, threshold, timelock), VotingError::InvalidAdminSet);
+        },
+        GovernanceAction::SetAuthority { .. }
+        | GovernanceAction::SetPollCreationFee { .. }
+        | GovernanceAction::SetBadgeThresholds { .. }
+        | GovernanceAction::RevokeBadge { .. } => {},
+    }
+
+    proposal.id = voting.proposal_count;
+    proposal.proposer = proposer;
+    proposal.action = action.clone();
+    proposal.governance_version = voting.governance_version;
+    proposal.approvals = 0;
+    proposal.created_at = moment;
+    proposal.approved_at = None;
+    proposal.executed_at = None;
+    proposal.bump = ctx.bumps.proposal;
+    proposal.approve(voting, &proposer, moment)?;
+
+    voting.proposal_count = voting.proposal_count
+        .checked_add(1)
+        .ok_or(VotingError::ArithmeticOverflow)?;
+
+    emit!(ProposalCreated {
+        proposal: proposal.key(),
+        id: proposal.id,
+        proposer,
+        action,
+        created_at: moment,
+    });
+
+    msg!("Governance proposal {} created", proposal.id);
+    Ok(())
+}
@@ -0,0 +1,84 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::error::*;
+use crate::utils::*;
+
+#[event]
+pub struct StandingDelegationCreated {
+    pub standing_delegation: Pubkey,
+    pub delegator: Pubkey,
+    pub delegatee: Pubkey,
+    pub tags: Vec<String>,
+    pub verifying_key: Pubkey,
+    pub derivation_key: Pubkey,
+    pub expires_at: Option<i64>,
+    pub created_at: i64,
+}
+
+#[derive(Accounts)]
+#[instruction(zk_proof: Vec<u8>, nullifier_hash: [u8; 32])]
+pub struct CreateStandingDelegation<'info> {
+    #[account(constraint = verifying_key.is_finalized @ VotingError::VerifyingKeyNotFinalized)]
+    pub verifying_key: Account<'info, VerifyingKey>,
+    // Circuit the delegatee proves the delegator's per-poll nullifiers with
+    #[account(constraint = derivation_key.is_finalized @ VotingError::VerifyingKeyNotFinalized)]
+    pub derivation_key: Account<'info, VerifyingKey>,
+    #[account(init, payer = o_delegator, space = StandingDelegation::MAX_SIZE, seeds = [b"standing_delegation", verifying_key.key().as_ref(), &nullifier_hash], bump)]
+    pub standing_delegation: Account<'info, StandingDelegation>,
+    #[account(seeds = [b"voting"], bump = voting.bump)]
+    pub voting: Account<'info, Voting>,
+    #[account(mut)]
+    pub o_delegator: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(
+    ctx: Context<CreateStandingDelegation>,
+    zk_proof: Vec<u8>,
+    nullifier_hash: [u8; 32],
+    public_signals: Vec<[u8; 32]>,
+    delegatee: Pubkey,
+    tags: Vec<String>,
+    expires_at: Option<i64>,
+) -> Result<()> {
+    ensure_operational(&ctx.accounts.voting, None)?;
+
+    let standing_delegation = &mut ctx.accounts.standing_delegation;
+    let delegator = ctx.accounts.o_delegator.key();
+    let verifying_key = ctx.accounts.verifying_key.key();
+    let moment = Clock::get()?.unix_timestamp;
+
+    require!(delegatee != delegator, VotingError::CannotDelegateToSelf);
+    require!(validate_tags(&tags), VotingError::InvalidTags);
+    if let Some(timeout) = expires_at {
+        require!(timeout > moment, VotingError::InvalidExpirationTime);
+    }
+
+    // bound to the circuit rather than a poll, see `StandingDelegation`
+    require!(!zk_proof.is_empty(), VotingError::InvalidZkProof);
+    require!(verify_zk_proof(&zk_proof, &public_signals, &ctx.accounts.verifying_key.groth16()?, &verifying_key, &nullifier_hash)?, VotingError::ZkProofVerificatiionFailed);
+
+    standing_delegation.delegator = delegator;
+    standing_delegation.delegatee = delegatee;
+    standing_delegation.tags = tags.clone();
+    standing_delegation.verifying_key = verifying_key;
+    standing_delegation.derivation_key = ctx.accounts.derivation_key.key();
+    standing_delegation.nullifier_hash = nullifier_hash;
+    standing_delegation.expires_at = expires_at;
+    standing_delegation.created_at = moment;
+    standing_delegation.bump = ctx.bumps.standing_delegation;
+
+    emit!(StandingDelegationCreated {
+        standing_delegation: standing_delegation.key(),
+        delegator,
+        delegatee,
+        tags,
+        verifying_key,
+        derivation_key: standing_delegation.derivation_key,
+        expires_at,
+        created_at: moment,
+    });
+
+    msg!("Standing delegation created");
+    Ok(())
+}
@@ -0,0 +1,68 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::error::*;
+use crate::utils::*;
+
+#[event]
+pub struct VerifyingKeyCreated {
+    pub verifying_key: Pubkey,
+    pub authority: Pubkey,
+    pub circuit_id: String,
+    pub public_inputs: u8,
+    pub created_at: i64,
+}
+
+#[derive(Accounts)]
+#[instruction(circuit_id: String, public_inputs: u8)]
+pub struct CreateVerifyingKey<'info> {
+    #[account(
+        init,
+        payer = authority,
+        space = VerifyingKey::space(public_inputs as usize),
+        seeds = [b"verifying_key", authority.key().as_ref(), circuit_id.as_bytes()],
+        bump
+    )]
+    pub verifying_key: Account<'info, VerifyingKey>,
+    #[account(seeds = [b"voting"], bump = voting.bump)]
+    pub voting: Account<'info, Voting>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<CreateVerifyingKey>, circuit_id: String, public_inputs: u8) -> Result<()> {
+    ensure_operational(&ctx.accounts.voting, None)?;
+
+    let verifying_key = &mut ctx.accounts.verifying_key;
+    let moment = Clock::get()?.unix_timestamp;
+
+    require!(!circuit_id.is_empty() && circuit_id.len() <= 32, VotingError::CircuitIdTooLong);
+
+    // nullifier hash and poll binding are always public inputs
+    require!(
+        public_inputs >= 2 && public_inputs as usize <= MAX_PUBLIC_INPUTS,
+        VotingError::InvalidVerifyingKey
+    );
+
+    verifying_key.authority = ctx.accounts.authority.key();
+    verifying_key.circuit_id = circuit_id.clone();
+    verifying_key.public_inputs = public_inputs;
+    verifying_key.is_finalized = false;
+    verifying_key.created_at = moment;
+    verifying_key.finalized_at = None;
+    verifying_key.bump = ctx.bumps.verifying_key;
+    verifying_key.data = vec![0; Groth16VerifyingKey::size(public_inputs as usize)];
+
+    emit!(VerifyingKeyCreated {
+        verifying_key: verifying_key.key(),
+        authority: ctx.accounts.authority.key(),
+        circuit_id: circuit_id.clone(),
+        public_inputs,
+        created_at: moment,
+    });
+
+    msg!("Verifying key '{}' created with {} public inputs", circuit_id, public_inputs);
+    Ok(())
+}
@@ -0,0 +1,38 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::error::*;
+
+#[event]
+pub struct EmergencyPaused {
+    pub authority: Pubkey,
+    pub reason: String,
+    pub paused_at: i64,
+}
+
+#[derive(Accounts)]
+pub struct EmergencyPause<'info> {
+    #[account(mut, seeds = [b"voting"], bump = voting.bump)]
+    pub voting: Account<'info, Voting>,
+    pub authority: Signer<'info>,
+}
+
+// Halts every state-changing instruction guarded by `ensure_operational`.
+// Once an admin set is configured this goes through a `Pause` proposal instead.
+pub fn handler(ctx: Context<EmergencyPause>, reason: String) -> Result<()> {
+    let voting = &mut ctx.accounts.voting;
+    let moment = Clock::get()?.unix_timestamp;
+
+    voting.ensure_single_authority(&ctx.accounts.authority.key())?;
+    require!(reason.len() <= MAX_GOVERNANCE_REASON, VotingError::DataTooLong);
+
+    voting.pause(moment)?;
+
+    emit!(EmergencyPaused {
+        authority: voting.authority,
+        reason,
+        paused_at: moment,
+    });
+
+    msg!("Operations paused");
+    Ok(())
+}
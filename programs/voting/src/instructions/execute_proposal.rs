@@ -0,0 +1,96 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::error::*;
+use crate::instructions::emergency_pause::EmergencyPaused;
+use crate::instructions::resume_operations::OperationsResumed;
+use crate::instructions::propose_authority::AuthorityProposed;
+use crate::instructions::set_badge_thresholds::BadgeThresholdsUpdated;
+
+#[event]
+pub struct ProposalExecuted {
+    pub proposal: Pubkey,
+    pub id: u64,
+    pub action: GovernanceAction,
+    pub executed_at: i64,
+}
+
+#[derive(Accounts)]
+pub struct ExecuteProposal<'info> {
+    #[account(mut, seeds = [b"voting"], bump = voting.bump)]
+    pub voting: Account<'info, Voting>,
+    #[account(mut, seeds = [b"proposal", &proposal.id.to_le_bytes()], bump = proposal.bump)]
+    pub proposal: Account<'info, GovernanceProposal>,
+    pub executor: Signer<'info>,
+}
+
+// Permissionless once approved and past the timelock. Badge awards and revocations need the
+// badge accounts and are executed by `award_badge` and `revoke_badge` with the proposal instead.
+pub fn handler(ctx: Context<ExecuteProposal>) -> Result<()> {
+    // no `ensure_operational`: governance stays usable while paused so a `Resume` can pass
+    let voting = &mut ctx.accounts.voting;
+    let proposal = &mut ctx.accounts.proposal;
+    let moment = Clock::get()?.unix_timestamp;
+
+    proposal.execute(voting, moment)?;
+
+    match proposal.action.clone() {
+        GovernanceAction::Pause { reason } => {
+            voting.pause(moment)?;
+            emit!(EmergencyPaused {
+                authority: voting.authority,
+                reason,
+                paused_at: moment,
+            });
+        },
+        GovernanceAction::Resume { reason } => {
+            let paused_at = voting.resume()?;
+            emit!(OperationsResumed {
+                authority: voting.authority,
+                reason,
+                paused_at,
+                resumed_at: moment,
+            });
+        },
+        GovernanceAction::SetAuthority { authority } => {
+            voting.pending_authority = Some(authority);
+            emit!(AuthorityProposed {
+                account: voting.key(),
+                current_authority: voting.authority,
+                proposed_authority: Some(authority),
+                proposed_at: moment,
+            });
+        },
+        GovernanceAction::SetPollCreationFee { fee } => {
+            voting.poll_creation_fee = fee;
+        },
+        GovernanceAction::SetBadgeThresholds { thresholds } => {
+            voting.badge_thresholds = thresholds.clone();
+            emit!(BadgeThresholdsUpdated {
+                thresholds,
+                updated_at: moment,
+            });
+        },
+        GovernanceAction::UpdateGovernance { admins, threshold, timelock } => {
+            // pending proposals were approved by the old set and become stale
+            voting.admins = admins;
+            voting.threshold = threshold;
+            voting.timelock = timelock;
+            voting.governance_version = voting.governance_version
+                .checked_add(1)
+                .ok_or(VotingError::ArithmeticOverflow)?;
+        },
+        GovernanceAction::AwardBadge { .. } | GovernanceAction::RevokeBadge { .. } => {
+            return err!(VotingError::InvalidGovernanceAction);
+        },
+    }
+
+    emit!(ProposalExecuted {
+        proposal: proposal.key(),
+        id: proposal.id,
+        action: proposal.action.clone(),
+        executed_at: moment,
+    });
+
+    msg!("Governance proposal {} executed", proposal.id);
+    Ok(())
+}
@@ -0,0 +1,62 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::error::*;
+use crate::utils::*;
+
+#[event]
+pub struct VerifyingKeyFinalized {
+    pub verifying_key: Pubkey,
+    pub authority: Pubkey,
+    pub circuit_id: String,
+    pub data_hash: [u8; 32],
+    pub finalized_at: i64,
+}
+
+#[derive(Accounts)]
+pub struct FinalizeVerifyingKey<'info> {
+    #[account(
+        mut,
+        seeds = [b"verifying_key", authority.key().as_ref(), verifying_key.circuit_id.as_bytes()],
+        bump = verifying_key.bump,
+        constraint = verifying_key.authority == authority.key() @ VotingError::UnauthorizedAccess,
+        constraint = !verifying_key.is_finalized @ VotingError::VerifyingKeyFinalized
+    )]
+    pub verifying_key: Account<'info, VerifyingKey>,
+    #[account(seeds = [b"voting"], bump = voting.bump)]
+    pub voting: Account<'info, Voting>,
+
+    pub authority: Signer<'info>,
+}
+
+pub fn handler(ctx: Context<FinalizeVerifyingKey>) -> Result<()> {
+    ensure_operational(&ctx.accounts.voting, None)?;
+
+    let verifying_key = &mut ctx.accounts.verifying_key;
+    let moment = Clock::get()?.unix_timestamp;
+
+    // the uploaded bytes must decode to a key with exactly the declared number of inputs
+    let key = Groth16VerifyingKey::try_from_slice(&verifying_key.data)
+        .map_err(|_| VotingError::InvalidVerifyingKey)?;
+    key.validate()?;
+    require!(
+        key.public_inputs_len() == verifying_key.public_inputs as usize,
+        VotingError::InvalidVerifyingKey
+    );
+
+    verifying_key.is_finalized = true;
+    verifying_key.finalized_at = Some(moment);
+
+    // lets clients check the uploaded key against the one from their trusted setup
+    let data_hash = hash_bytes(&verifying_key.data);
+
+    emit!(VerifyingKeyFinalized {
+        verifying_key: verifying_key.key(),
+        authority: verifying_key.authority,
+        circuit_id: verifying_key.circuit_id.clone(),
+        data_hash,
+        finalized_at: moment,
+    });
+
+    msg!("Verifying key '{}' finalized", verifying_key.circuit_id);
+    Ok(())
+}
@@ -18,16 +18,25 @@ pub struct Initialize<'info> {
 }
 
 pub fn handler(ctx: Context<Initialize>) -> Result<()> {
+    // no `ensure_operational`: the config it checks is created here
     let voting = &mut ctx.accounts.voting;
 
     voting.authority = ctx.accounts.authority.key();
+    voting.pending_authority = None;
     voting.total_polls = 0;
     voting.total_votes = 0;
-    voting.total_voters = 0;
     voting.total_users = 0;
     voting.created_at = Clock::get()?.unix_timestamp;
     voting.is_paused = false;
-    voting.bump = *ctx.bumps.voting;
+    voting.paused_at = None;
+    voting.admins = Vec::new();
+    voting.threshold = 0;
+    voting.timelock = 0;
+    voting.governance_version = 0;
+    voting.proposal_count = 0;
+    voting.poll_creation_fee = 0;
+    voting.badge_thresholds = BadgeThresholds::DEFAULT;
+    voting.bump = ctx.bumps.voting;
 
     msg!("Voting initialized successfully");
     Ok(())
@@ -0,0 +1,83 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::error::*;
+
+#[event]
+pub struct UserRegistered {
+    pub user: Pubkey,
+    pub did_identifier: Option<String>,
+    pub registered_at: i64,
+}
+
+#[derive(Accounts)]
+pub struct InitializeUser<'info> {
+    #[account(init, payer = authority, space = User::MAX_SIZE, seeds = [b"user", authority.key().as_ref()], bump)]
+    pub user: Account<'info, User>,
+    #[account(init, payer = authority, space = UserStats::MAX_SIZE, seeds = [b"user_stats", authority.key().as_ref()], bump)]
+    pub user_stats: Account<'info, UserStats>,
+    #[account(mut, seeds = [b"voting"], bump = voting.bump)]
+    pub voting: Account<'info, Voting>,
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(
+    ctx: Context<InitializeUser>,
+    did_identifier: Option<String>,
+    location_hash: Option<[u8; 32]>,
+    preferred_language: Option<String>,
+) -> Result<()> {
+    ensure_operational(&ctx.accounts.voting, None)?;
+
+    let user = &mut ctx.accounts.user;
+    let user_stats = &mut ctx.accounts.user_stats;
+    let voting = &mut ctx.accounts.voting;
+    let moment = Clock::get()?.unix_timestamp;
+
+    if let Some(ref did) = did_identifier {
+        require!(did.len() <= 100, VotingError::DataTooLong);
+    }
+    if let Some(ref language) = preferred_language {
+        require!(language.len() <= 10, VotingError::DataTooLong);
+    }
+
+    user.pubkey = ctx.accounts.authority.key();
+    user.did_identifier = did_identifier.clone();
+    user.total_votes_cast = 0;
+    user.total_polls_created = 0;
+    user.total_delegations_given = 0;
+    user.total_delegations_received = 0;
+    user.reputation_score = 0;
+    user.is_verified = false;
+    user.verification_level = 0;
+    user.created_at = moment;
+    user.last_activity = moment;
+    user.preferred_language = preferred_language;
+    user.location_hash = location_hash;
+    user.badges_earned = Vec::new();
+    user.is_active = true;
+    user.bump = ctx.bumps.user;
+
+    user_stats.user = user.pubkey;
+    user_stats.polls_participated = Vec::new();
+    user_stats.avg_participation_time = 0;
+    user_stats.streak_count = 0;
+    user_stats.last_streak_date = 0;
+    user_stats.civic_engagement_score = 0;
+    user_stats.hide_voting_history = false;
+    user_stats.bump = ctx.bumps.user_stats;
+
+    voting.total_users = voting.total_users
+        .checked_add(1)
+        .ok_or(VotingError::ArithmeticOverflow)?;
+
+    emit!(UserRegistered {
+        user: user.pubkey,
+        did_identifier,
+        registered_at: moment,
+    });
+
+    msg!("User registered: {}", user.pubkey);
+    Ok(())
+}
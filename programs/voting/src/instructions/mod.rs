@@ -1,15 +1,94 @@
+// Every instruction module exposes its own `handler`; lib.rs always calls them by path.
+#![allow(ambiguous_glob_reexports)]
+
 pub mod initialize;
+pub mod initialize_user;
+pub mod create_user_preferences;
+pub mod update_user_preferences;
+pub mod close_user_preferences;
+pub mod emergency_pause;
+pub mod resume_operations;
+pub mod set_poll_paused;
+pub mod set_weight_snapshot;
+pub mod set_allowlist_root;
+pub mod configure_governance;
+pub mod create_proposal;
+pub mod approve_proposal;
+pub mod execute_proposal;
+pub mod propose_authority;
+pub mod accept_authority;
+pub mod propose_poll_authority;
+pub mod accept_poll_authority;
 pub mod create_poll;
 pub mod close_poll;
+pub mod unlock_tokens;
+pub mod activate_poll;
+pub mod cancel_poll;
 pub mod cast_vote;
 pub mod recast_vote;
 pub mod vote_delegation;
+pub mod cast_delegated_vote;
+pub mod revoke_delegation;
+pub mod redelegate;
+pub mod create_standing_delegation;
+pub mod revoke_standing_delegation;
+pub mod apply_standing_delegation;
+pub mod override_standing_delegation;
 pub mod award_badge;
+pub mod revoke_badge;
+pub mod set_badge_thresholds;
+pub mod create_verifying_key;
+pub mod write_verifying_key;
+pub mod finalize_verifying_key;
+pub mod tally_ranked_choice;
+pub mod reveal_vote;
+pub mod setup_encrypted_tally;
+pub mod prove_encrypted_ballot;
+pub mod submit_decryption_share;
+pub mod publish_tally;
 
 pub use initialize::*;
+pub use initialize_user::*;
+pub use create_user_preferences::*;
+pub use update_user_preferences::*;
+pub use close_user_preferences::*;
+pub use emergency_pause::*;
+pub use resume_operations::*;
+pub use set_poll_paused::*;
+pub use set_weight_snapshot::*;
+pub use set_allowlist_root::*;
+pub use configure_governance::*;
+pub use create_proposal::*;
+pub use approve_proposal::*;
+pub use execute_proposal::*;
+pub use propose_authority::*;
+pub use accept_authority::*;
+pub use propose_poll_authority::*;
+pub use accept_poll_authority::*;
 pub use create_poll::*;
 pub use close_poll::*;
+pub use unlock_tokens::*;
+pub use activate_poll::*;
+pub use cancel_poll::*;
 pub use cast_vote::*;
 pub use recast_vote::*;
 pub use vote_delegation::*;
+pub use cast_delegated_vote::*;
+pub use revoke_delegation::*;
+pub use redelegate::*;
+pub use create_standing_delegation::*;
+pub use revoke_standing_delegation::*;
+pub use apply_standing_delegation::*;
+pub use override_standing_delegation::*;
 pub use award_badge::*;
+pub use revoke_badge::*;
+pub use set_badge_thresholds::*;
+pub use create_verifying_key::*;
+pub use write_verifying_key::*;
+pub use finalize_verifying_key::*;
+pub use tally_ranked_choice::*;
+pub use reveal_vote::*;
+pub use setup_encrypted_tally::*;
+pub use prove_encrypted_ballot::*;
+pub use submit_decryption_share::*;
+pub use publish_tally::*;
@@ -0,0 +1,50 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::error::*;
+
+#[event]
+pub struct StandingDelegationOverridden {
+    pub poll: Pubkey,
+    pub delegator: Pubkey,
+    pub delegatee: Pubkey,
+    pub overridden_at: i64,
+}
+
+#[derive(Accounts)]
+#[instruction(nullifier_hash: [u8; 32])]
+pub struct OverrideStandingDelegation<'info> {
+    pub poll: Account<'info, Poll>,
+    #[account(seeds = [b"voting"], bump = voting.bump)]
+    pub voting: Account<'info, Voting>,
+    #[account(
+        mut,
+        seeds = [b"delegation", poll.key().as_ref(), &nullifier_hash],
+        bump = vote_delegation.bump,
+        constraint = vote_delegation.o_delegator == o_delegator.key() @ VotingError::UnauthorizedAccess,
+        constraint = vote_delegation.used_in.is_none() @ VotingError::DelegationAlreadyUsed
+    )]
+    pub vote_delegation: Account<'info, VoteDelegation>,
+    pub o_delegator: Signer<'info>,
+}
+
+// Takes back, for one poll, the power a standing delegation gave the delegatee. The applied
+// delegation is deactivated rather than closed so it cannot be applied again in this poll.
+pub fn handler(ctx: Context<OverrideStandingDelegation>, _nullifier_hash: [u8; 32]) -> Result<()> {
+    ensure_operational(&ctx.accounts.voting, Some(&ctx.accounts.poll))?;
+
+    let poll = &ctx.accounts.poll;
+    let vote_delegation = &mut ctx.accounts.vote_delegation;
+    let moment = Clock::get()?.unix_timestamp;
+
+    vote_delegation.is_active = false;
+
+    emit!(StandingDelegationOverridden {
+        poll: poll.key(),
+        delegator: vote_delegation.o_delegator,
+        delegatee: vote_delegation.o_delegatee,
+        overridden_at: moment,
+    });
+
+    msg!("Standing delegation overridden for poll: {}", poll.poll_name);
+    Ok(())
+}
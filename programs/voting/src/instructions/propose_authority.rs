@@ -0,0 +1,39 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+
+// Shared by the global config and polls; `account` is the one whose authority changes
+#[event]
+pub struct AuthorityProposed {
+    pub account: Pubkey,
+    pub current_authority: Pubkey,
+    pub proposed_authority: Option<Pubkey>,
+    pub proposed_at: i64,
+}
+
+#[derive(Accounts)]
+pub struct ProposeAuthority<'info> {
+    #[account(mut, seeds = [b"voting"], bump = voting.bump)]
+    pub voting: Account<'info, Voting>,
+    pub authority: Signer<'info>,
+}
+
+// First step of a transfer of the global authority; `None` cancels a pending transfer.
+// Once an admin set is configured this goes through a `SetAuthority` proposal instead.
+pub fn handler(ctx: Context<ProposeAuthority>, new_authority: Option<Pubkey>) -> Result<()> {
+    // no `ensure_operational`: a compromised authority must still be replaceable while paused
+    let voting = &mut ctx.accounts.voting;
+    let moment = Clock::get()?.unix_timestamp;
+
+    voting.ensure_single_authority(&ctx.accounts.authority.key())?;
+    voting.pending_authority = new_authority;
+
+    emit!(AuthorityProposed {
+        account: voting.key(),
+        current_authority: voting.authority,
+        proposed_authority: new_authority,
+        proposed_at: moment,
+    });
+
+    msg!("Global authority transfer proposed");
+    Ok(())
+}
@@ -0,0 +1,34 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::error::*;
+use crate::instructions::propose_authority::AuthorityProposed;
+
+#[derive(Accounts)]
+pub struct ProposePollAuthority<'info> {
+    #[account(mut, constraint = poll.authority == authority.key() @ VotingError::UnauthorizedAccess)]
+    pub poll: Account<'info, Poll>,
+    #[account(seeds = [b"voting"], bump = voting.bump)]
+    pub voting: Account<'info, Voting>,
+    pub authority: Signer<'info>,
+}
+
+// First step of moving a poll to another wallet; `None` cancels a pending transfer
+pub fn handler(ctx: Context<ProposePollAuthority>, new_authority: Option<Pubkey>) -> Result<()> {
+    ensure_operational(&ctx.accounts.voting, Some(&ctx.accounts.poll))?;
+
+    let poll = &mut ctx.accounts.poll;
+    let moment = Clock::get()?.unix_timestamp;
+
+    poll.pending_authority = new_authority;
+    poll.updated_at = moment;
+
+    emit!(AuthorityProposed {
+        account: poll.key(),
+        current_authority: poll.authority,
+        proposed_authority: new_authority,
+        proposed_at: moment,
+    });
+
+    msg!("Authority transfer proposed for poll {}", poll.poll_name);
+    Ok(())
+}
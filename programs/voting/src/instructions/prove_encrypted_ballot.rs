@@ -0,0 +1,98 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::error::*;
+use crate::utils::*;
+
+#[event]
+pub struct EncryptedBallotCounted {
+    pub poll: Pubkey,
+    pub nullifier_hash: [u8; 32],
+    pub ballots_aggregated: u64,
+    pub counted_at: i64,
+}
+
+#[derive(Accounts)]
+#[instruction(nullifier_hash: [u8; 32])]
+pub struct ProveEncryptedBallot<'info> {
+    #[account(mut, constraint = poll.status == PollStatus::Active @ VotingError::PollNotActive)]
+    pub poll: Account<'info, Poll>,
+    #[account(seeds = [b"voting"], bump = voting.bump)]
+    pub voting: Account<'info, Voting>,
+
+    #[account(
+        mut,
+        seeds = [b"encrypted_tally", poll.key().as_ref()],
+        bump = encrypted_tally.bump
+    )]
+    pub encrypted_tally: Account<'info, EncryptedTally>,
+
+    #[account(
+        mut,
+        seeds = [b"vote", poll.key().as_ref(), &nullifier_hash],
+        bump = vote.bump,
+        constraint = vote.tallied_round == 0 @ VotingError::BallotAlreadyRevealed
+    )]
+    pub vote: Account<'info, Vote>,
+
+    pub prover: Signer<'info>,
+}
+
+// Second half of an encrypted vote, split out because the proofs do not fit in the
+// `cast_vote` transaction. Checks that every ciphertext encrypts 0 or 1 and that they sum
+// to 1, then adds the ballot to the poll's encrypted aggregate. Permissionless: the proofs
+// are bound to the poll, nullifier and option, so relaying them reveals nothing.
+pub fn handler(
+    ctx: Context<ProveEncryptedBallot>,
+    nullifier_hash: [u8; 32],
+    proofs: Vec<ZeroOneProof>,
+    sum_proof: EqualityProof,
+) -> Result<()> {
+    ensure_operational(&ctx.accounts.voting, Some(&ctx.accounts.poll))?;
+
+    let poll = &mut ctx.accounts.poll;
+    let encrypted_tally = &mut ctx.accounts.encrypted_tally;
+    let vote = &mut ctx.accounts.vote;
+    let moment = Clock::get()?.unix_timestamp;
+
+    let ciphertexts = match vote.ballot {
+        Ballot::Encrypted(ref ciphertexts) => ciphertexts.clone(),
+        _ => return err!(VotingError::InvalidBallotType),
+    };
+    require!(proofs.len() == ciphertexts.len(), VotingError::InvalidEncryptionProof);
+
+    let election_key = encrypted_tally.election_public_key;
+    let mut context = Vec::with_capacity(32 + 32 + 1);
+    context.extend_from_slice(poll.key().as_ref());
+    context.extend_from_slice(&nullifier_hash);
+
+    for (option, (ciphertext, proof)) in ciphertexts.iter().zip(proofs.iter()).enumerate() {
+        context.push(option as u8);
+        verify_zero_one_proof(ciphertext, &election_key, &context, proof)?;
+        context.pop();
+    }
+    verify_sum_is_one(&ciphertexts, &election_key, &context, &sum_proof)?;
+
+    for (aggregate, ciphertext) in encrypted_tally.aggregate.iter_mut().zip(ciphertexts.iter()) {
+        *aggregate = aggregate.add(ciphertext)?;
+    }
+    encrypted_tally.ballots_aggregated = encrypted_tally.ballots_aggregated
+        .checked_add(1)
+        .ok_or(VotingError::ArithmeticOverflow)?;
+
+    poll.revealed_votes = poll.revealed_votes
+        .checked_add(1)
+        .ok_or(VotingError::ArithmeticOverflow)?;
+    poll.updated_at = moment;
+
+    vote.tallied_round = 1;
+
+    emit!(EncryptedBallotCounted {
+        poll: poll.key(),
+        nullifier_hash,
+        ballots_aggregated: encrypted_tally.ballots_aggregated,
+        counted_at: moment,
+    });
+
+    msg!("Encrypted ballot counted for poll {}", poll.poll_name);
+    Ok(())
+}
@@ -0,0 +1,92 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::error::*;
+use crate::utils::*;
+
+#[event]
+pub struct TallyPublished {
+    pub poll: Pubkey,
+    pub vote_counts: Vec<u64>,
+    pub ballots_counted: u64,
+    pub trustees: Vec<u8>,
+    pub published_at: i64,
+}
+
+#[derive(Accounts)]
+pub struct PublishTally<'info> {
+    #[account(mut, constraint = poll.status == PollStatus::Closed @ VotingError::PollNotClosed)]
+    pub poll: Account<'info, Poll>,
+    #[account(seeds = [b"voting"], bump = voting.bump)]
+    pub voting: Account<'info, Voting>,
+
+    #[account(
+        mut,
+        seeds = [b"encrypted_tally", poll.key().as_ref()],
+        bump = encrypted_tally.bump,
+        constraint = !encrypted_tally.is_published @ VotingError::TallyAlreadyComplete
+    )]
+    pub encrypted_tally: Account<'info, EncryptedTally>,
+
+    #[account(mut, seeds = [b"poll_result", poll.key().as_ref()], bump = poll_result.bump)]
+    pub poll_result: Account<'info, PollResult>,
+
+    pub publisher: Signer<'info>,
+}
+
+// Permissionless once `threshold` trustees have posted shares: the claimed totals are only
+// accepted if totals[j]·G equals the aggregate of option j decrypted with those shares.
+pub fn handler(ctx: Context<PublishTally>, totals: Vec<u64>) -> Result<()> {
+    ensure_operational(&ctx.accounts.voting, Some(&ctx.accounts.poll))?;
+
+    let poll = &mut ctx.accounts.poll;
+    let encrypted_tally = &mut ctx.accounts.encrypted_tally;
+    let moment = Clock::get()?.unix_timestamp;
+
+    require!(
+        encrypted_tally.decryption_shares.len() >= encrypted_tally.threshold as usize,
+        VotingError::NotEnoughDecryptionShares
+    );
+    require!(totals.len() == encrypted_tally.aggregate.len(), VotingError::InvalidDecryptionShare);
+
+    for (option, (aggregate, &total)) in encrypted_tally.aggregate.iter().zip(totals.iter()).enumerate() {
+        let trustee_shares: Vec<(u8, [u8; 32])> = encrypted_tally
+            .decryption_shares
+            .iter()
+            .map(|share| (share.trustee_index, share.shares[option]))
+            .collect();
+        let decryption = interpolate(&trustee_shares)?;
+
+        require!(
+            verify_decrypted_total(aggregate, &decryption, total)?,
+            VotingError::InvalidDecryptionShare
+        );
+    }
+
+    // every proven ballot encrypts exactly one vote
+    let ballots_counted = totals
+        .iter()
+        .try_fold(0u64, |sum, &total| sum.checked_add(total))
+        .ok_or(VotingError::ArithmeticOverflow)?;
+    require!(ballots_counted == encrypted_tally.ballots_aggregated, VotingError::InvalidDecryptionShare);
+
+    poll.vote_counts = totals.clone();
+    poll.total_votes = ballots_counted;
+    poll.updated_at = moment;
+
+    ctx.accounts.poll_result.finalize(totals.clone(), PollResult::leaders(&totals), moment);
+    ctx.accounts.poll_result.total_votes = ballots_counted;
+
+    encrypted_tally.is_published = true;
+    encrypted_tally.published_at = Some(moment);
+
+    emit!(TallyPublished {
+        poll: poll.key(),
+        vote_counts: totals,
+        ballots_counted,
+        trustees: encrypted_tally.decryption_shares.iter().map(|share| share.trustee_index).collect(),
+        published_at: moment,
+    });
+
+    msg!("Decrypted tally published for poll {}", poll.poll_name);
+    Ok(())
+}
@@ -0,0 +1,100 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::error::*;
+use crate::utils::*;
+
+#[event]
+pub struct DelegationChanged {
+    pub poll: Pubkey,
+    pub delegator: Pubkey,
+    pub old_delegatee: Pubkey,
+    pub new_delegatee: Pubkey,
+    pub nullifier_hash: [u8; 32],
+    pub expires_at: Option<i64>,
+    pub chain_length: u8,
+    pub changed_at: i64,
+}
+
+#[derive(Accounts)]
+#[instruction(zk_proof: Vec<u8>, nullifier_hash: [u8; 32])]
+pub struct Redelegate<'info> {
+    #[account(constraint = poll.is_active() @ VotingError::PollNotActive)]
+    pub poll: Account<'info, Poll>,
+    #[account(seeds = [b"voting"], bump = voting.bump)]
+    pub voting: Account<'info, Voting>,
+    #[account(address = poll.verifying_key @ VotingError::InvalidVerifyingKey)]
+    pub verifying_key: Account<'info, VerifyingKey>,
+    #[account(
+        mut,
+        seeds = [b"delegation", poll.key().as_ref(), &nullifier_hash],
+        bump = vote_delegation.bump,
+        constraint = vote_delegation.o_delegator == o_delegator.key() @ VotingError::UnauthorizedAccess,
+        constraint = vote_delegation.is_active @ VotingError::DelegationNotActive,
+        constraint = vote_delegation.used_in.is_none() @ VotingError::DelegationAlreadyUsed
+    )]
+    pub vote_delegation: Account<'info, VoteDelegation>,
+    #[account(
+        mut,
+        seeds = [b"delegation_route", poll.key().as_ref(), o_delegator.key().as_ref()],
+        bump = delegation_route.bump
+    )]
+    pub delegation_route: Account<'info, DelegationRoute>,
+    pub o_delegator: Signer<'info>,
+}
+
+// Points an unused delegation at a new delegatee. The new delegatee's chain is passed as
+// remaining accounts, as for `delegate_vote`.
+pub fn handler(
+    ctx: Context<Redelegate>,
+    zk_proof: Vec<u8>,
+    nullifier_hash: [u8; 32],
+    public_signals: Vec<[u8; 32]>,
+    new_delegatee: Pubkey,
+    expires_at: Option<i64>,
+) -> Result<()> {
+    ensure_operational(&ctx.accounts.voting, Some(&ctx.accounts.poll))?;
+
+    let poll = &ctx.accounts.poll;
+    let vote_delegation = &mut ctx.accounts.vote_delegation;
+    let delegation_route = &mut ctx.accounts.delegation_route;
+    let delegator = ctx.accounts.o_delegator.key();
+    let moment = Clock::get()?.unix_timestamp;
+
+    require!(moment <= poll.poll_end_time, VotingError::VotingEnded);
+    require!(new_delegatee != delegator, VotingError::CannotDelegateToSelf);
+
+    require!(!zk_proof.is_empty(), VotingError::InvalidZkProof);
+    require!(verify_zk_proof(&zk_proof, &public_signals, &ctx.accounts.verifying_key.groth16()?, &poll.key(), &nullifier_hash)?, VotingError::ZkProofVerificatiionFailed);
+
+    if let Some(timeout) = expires_at {
+        require!(timeout > moment, VotingError::InvalidExpirationTime);
+        require!(timeout <= poll.poll_end_time, VotingError::ExpirationAfterVotingEnded);
+    }
+
+    let chain_length = DelegationRoute::chain_length(
+        &poll.key(),
+        &delegator,
+        &new_delegatee,
+        poll.max_delegation_depth,
+        ctx.remaining_accounts,
+    )?;
+
+    let old_delegatee = vote_delegation.o_delegatee;
+    vote_delegation.o_delegatee = new_delegatee;
+    vote_delegation.expires_at = expires_at;
+    delegation_route.delegatee = new_delegatee;
+
+    emit!(DelegationChanged {
+        poll: poll.key(),
+        delegator,
+        old_delegatee,
+        new_delegatee,
+        nullifier_hash,
+        expires_at,
+        chain_length,
+        changed_at: moment,
+    });
+
+    msg!("Delegation updated for poll: {}", poll.poll_name);
+    Ok(())
+}
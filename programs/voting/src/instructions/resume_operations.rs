@@ -0,0 +1,39 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::error::*;
+
+#[event]
+pub struct OperationsResumed {
+    pub authority: Pubkey,
+    pub reason: String,
+    pub paused_at: Option<i64>,
+    pub resumed_at: i64,
+}
+
+#[derive(Accounts)]
+pub struct ResumeOperations<'info> {
+    #[account(mut, seeds = [b"voting"], bump = voting.bump)]
+    pub voting: Account<'info, Voting>,
+    pub authority: Signer<'info>,
+}
+
+pub fn handler(ctx: Context<ResumeOperations>, reason: String) -> Result<()> {
+    // no `ensure_operational`: this is how the global pause is lifted
+    let voting = &mut ctx.accounts.voting;
+    let moment = Clock::get()?.unix_timestamp;
+
+    voting.ensure_single_authority(&ctx.accounts.authority.key())?;
+    require!(reason.len() <= MAX_GOVERNANCE_REASON, VotingError::DataTooLong);
+
+    let paused_at = voting.resume()?;
+
+    emit!(OperationsResumed {
+        authority: voting.authority,
+        reason,
+        paused_at,
+        resumed_at: moment,
+    });
+
+    msg!("Operations resumed");
+    Ok(())
+}
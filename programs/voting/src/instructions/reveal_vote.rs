@@ -0,0 +1,75 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::error::*;
+use crate::utils::*;
+
+#[event]
+pub struct VoteRevealed {
+    pub poll: Pubkey,
+    pub nullifier_hash: [u8; 32],
+    pub ballot: Ballot,
+    pub vote_weight: u64,
+    pub revealed_at: i64,
+}
+
+#[derive(Accounts)]
+#[instruction(ballot: Ballot, salt: [u8; 32], nullifier_hash: [u8; 32])]
+pub struct RevealVote<'info> {
+    #[account(mut, constraint = poll.reveal_window.is_some() @ VotingError::OperationNotAllowed)]
+    pub poll: Account<'info, Poll>,
+    #[account(seeds = [b"voting"], bump = voting.bump)]
+    pub voting: Account<'info, Voting>,
+
+    #[account(
+        mut,
+        seeds = [b"vote", poll.key().as_ref(), &nullifier_hash],
+        bump = vote.bump,
+        constraint = vote.nullifier_hash == nullifier_hash @ VotingError::InvalidNullifierHash
+    )]
+    pub vote: Account<'info, Vote>,
+
+    pub revealer: Signer<'info>,
+}
+
+// Opens a sealed ballot during the reveal window. Knowing the ballot and salt is the only
+// authorization needed, so reveals can be relayed without linking a wallet to the vote.
+pub fn handler(ctx: Context<RevealVote>, ballot: Ballot, salt: [u8; 32], nullifier_hash: [u8; 32]) -> Result<()> {
+    ensure_operational(&ctx.accounts.voting, Some(&ctx.accounts.poll))?;
+
+    let poll = &mut ctx.accounts.poll;
+    let vote = &mut ctx.accounts.vote;
+    let moment = Clock::get()?.unix_timestamp;
+
+    require!(poll.status == PollStatus::Active, VotingError::PollNotActive);
+    require!(poll.is_reveal_phase(), VotingError::NotInRevealPhase);
+
+    let commitment = match vote.ballot {
+        Ballot::Committed(commitment) => commitment,
+        _ => return err!(VotingError::BallotAlreadyRevealed),
+    };
+    require!(
+        ballot_commitment(&ballot, &salt, &nullifier_hash)? == commitment,
+        VotingError::InvalidCommitment
+    );
+
+    ballot.validate_contents(poll)?;
+    poll.record_ballot(&ballot, vote.vote_weight)?;
+    poll.revealed_votes = poll.revealed_votes
+        .checked_add(1)
+        .ok_or(VotingError::ArithmeticOverflow)?;
+    poll.updated_at = moment;
+
+    vote.ballot = ballot.clone();
+    vote.tallied_round = 1;
+
+    emit!(VoteRevealed {
+        poll: poll.key(),
+        nullifier_hash,
+        ballot,
+        vote_weight: vote.vote_weight,
+        revealed_at: moment,
+    });
+
+    msg!("Vote revealed for poll {}", poll.poll_name);
+    Ok(())
+}
@@ -0,0 +1,114 @@
+use anchor_lang::prelude::*;
+use anchor_spl::associated_token::get_associated_token_address_with_program_id;
+use anchor_spl::token_interface::{self, Token2022};
+use crate::state::*;
+use crate::error::*;
+
+#[event]
+pub struct BadgeRevoked {
+    pub recipient: Pubkey,
+    pub badge_type: BadgeType,
+    pub mint: Option<Pubkey>,
+    pub revoked_at: i64,
+}
+
+#[derive(Accounts)]
+#[instruction(badge_type: BadgeType, recipient: Pubkey)]
+pub struct RevokeBadge<'info> {
+    // Kept rather than closed so the badge cannot be earned again automatically
+    #[account(
+        mut,
+        seeds = [b"badge", recipient.as_ref(), &[badge_type as u8]],
+        bump = user_badge.bump,
+        constraint = user_badge.is_active @ VotingError::BadgeNotActive
+    )]
+    pub user_badge: Account<'info, UserBadge>,
+    #[account(seeds = [b"voting"], bump = voting.bump)]
+    pub voting: Account<'info, Voting>,
+    // Empty when the recipient never created a profile; otherwise the badge is taken off it
+    /// CHECK: address fixed by the seeds, deserialized in the handler when it exists
+    #[account(mut, seeds = [b"user", recipient.as_ref()], bump)]
+    pub user: UncheckedAccount<'info>,
+
+    // Required once an admin set is configured: an approved `RevokeBadge` proposal for this badge
+    #[account(mut, seeds = [b"proposal", &proposal.id.to_le_bytes()], bump = proposal.bump)]
+    pub proposal: Option<Account<'info, GovernanceProposal>>,
+
+    pub authority: Signer<'info>,
+
+    // Only needed when the badge was minted as a token
+    /// CHECK: must be the mint recorded on the badge
+    #[account(mut)]
+    pub badge_mint: Option<UncheckedAccount<'info>>,
+    /// CHECK: checked against the recipient's associated token address
+    #[account(mut)]
+    pub recipient_token_account: Option<UncheckedAccount<'info>>,
+    pub token_program: Option<Program<'info, Token2022>>,
+}
+
+// The token is burnt by the config PDA as the mint's permanent delegate, no recipient signature needed
+pub fn handler(ctx: Context<RevokeBadge>, badge_type: BadgeType, recipient: Pubkey) -> Result<()> {
+    ensure_operational(&ctx.accounts.voting, None)?;
+
+    let voting = &ctx.accounts.voting;
+    let moment = Clock::get()?.unix_timestamp;
+
+    if voting.has_governance() {
+        let proposal = ctx.accounts.proposal.as_mut().ok_or(VotingError::GovernanceRequired)?;
+        let expected = GovernanceAction::RevokeBadge { badge_type, recipient };
+        require!(proposal.action == expected, VotingError::ProposalActionMismatch);
+        proposal.execute(voting, moment)?;
+    } else {
+        voting.ensure_single_authority(&ctx.accounts.authority.key())?;
+    }
+
+    let user_badge = &mut ctx.accounts.user_badge;
+    if let Some(mint) = user_badge.mint {
+        let (mint_info, token_account, token_program) = match (
+            ctx.accounts.badge_mint.as_ref(),
+            ctx.accounts.recipient_token_account.as_ref(),
+            ctx.accounts.token_program.as_ref(),
+        ) {
+            (Some(mint_info), Some(token_account), Some(token_program)) => {
+                (mint_info.to_account_info(), token_account.to_account_info(), token_program.to_account_info())
+            },
+            _ => return err!(VotingError::MissingBadgeTokenAccounts),
+        };
+        require!(mint_info.key() == mint, VotingError::InvalidBadgeTokenAccount);
+        require!(
+            token_account.key() == get_associated_token_address_with_program_id(&recipient, &mint, &token_program.key()),
+            VotingError::InvalidBadgeTokenAccount
+        );
+
+        token_interface::burn(
+            CpiContext::new_with_signer(
+                token_program,
+                token_interface::Burn {
+                    mint: mint_info,
+                    from: token_account,
+                    authority: voting.to_account_info(),
+                },
+                &[&[b"voting", &[voting.bump]]],
+            ),
+            1,
+        )?;
+    }
+
+    user_badge.is_active = false;
+
+    if !ctx.accounts.user.data_is_empty() {
+        let mut data = ctx.accounts.user.try_borrow_mut_data()?;
+        let mut user = User::try_deserialize(&mut &data[..])?;
+        user.remove_badge(badge_type);
+        user.try_serialize(&mut &mut data[..])?;
+    }
+
+    emit!(BadgeRevoked {
+        recipient,
+        badge_type,
+        mint: user_badge.mint,
+        revoked_at: moment,
+    });
+    msg!("Badge {:?} revoked from {}", badge_type, recipient);
+    Ok(())
+}
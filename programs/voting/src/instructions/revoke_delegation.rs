@@ -0,0 +1,70 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::error::*;
+use crate::utils::*;
+
+#[event]
+pub struct DelegationRevoked {
+    pub poll: Pubkey,
+    pub delegator: Pubkey,
+    pub delegatee: Pubkey,
+    pub nullifier_hash: [u8; 32],
+    pub revoked_at: i64,
+}
+
+#[derive(Accounts)]
+#[instruction(zk_proof: Vec<u8>, nullifier_hash: [u8; 32])]
+pub struct RevokeDelegation<'info> {
+    pub poll: Account<'info, Poll>,
+    #[account(seeds = [b"voting"], bump = voting.bump)]
+    pub voting: Account<'info, Voting>,
+    #[account(address = poll.verifying_key @ VotingError::InvalidVerifyingKey)]
+    pub verifying_key: Account<'info, VerifyingKey>,
+    #[account(
+        mut,
+        close = o_delegator,
+        seeds = [b"delegation", poll.key().as_ref(), &nullifier_hash],
+        bump = vote_delegation.bump,
+        constraint = vote_delegation.o_delegator == o_delegator.key() @ VotingError::UnauthorizedAccess,
+        constraint = vote_delegation.used_in.is_none() @ VotingError::DelegationAlreadyUsed
+    )]
+    pub vote_delegation: Account<'info, VoteDelegation>,
+    #[account(
+        mut,
+        close = o_delegator,
+        seeds = [b"delegation_route", poll.key().as_ref(), o_delegator.key().as_ref()],
+        bump = delegation_route.bump
+    )]
+    pub delegation_route: Account<'info, DelegationRoute>,
+    #[account(mut)]
+    pub o_delegator: Signer<'info>,
+}
+
+// Withdraws a delegation the delegatee has not cast yet and refunds its rent. The delegator
+// keeps an unspent nullifier and may vote directly or delegate again.
+pub fn handler(
+    ctx: Context<RevokeDelegation>,
+    zk_proof: Vec<u8>,
+    nullifier_hash: [u8; 32],
+    public_signals: Vec<[u8; 32]>,
+) -> Result<()> {
+    ensure_operational(&ctx.accounts.voting, Some(&ctx.accounts.poll))?;
+
+    let poll = &ctx.accounts.poll;
+    let vote_delegation = &ctx.accounts.vote_delegation;
+    let moment = Clock::get()?.unix_timestamp;
+
+    require!(!zk_proof.is_empty(), VotingError::InvalidZkProof);
+    require!(verify_zk_proof(&zk_proof, &public_signals, &ctx.accounts.verifying_key.groth16()?, &poll.key(), &nullifier_hash)?, VotingError::ZkProofVerificatiionFailed);
+
+    emit!(DelegationRevoked {
+        poll: poll.key(),
+        delegator: vote_delegation.o_delegator,
+        delegatee: vote_delegation.o_delegatee,
+        nullifier_hash,
+        revoked_at: moment,
+    });
+
+    msg!("Delegation revoked for poll: {}", poll.poll_name);
+    Ok(())
+}
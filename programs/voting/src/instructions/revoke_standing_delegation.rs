@@ -0,0 +1,44 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::error::*;
+
+#[event]
+pub struct StandingDelegationRevoked {
+    pub standing_delegation: Pubkey,
+    pub delegator: Pubkey,
+    pub delegatee: Pubkey,
+    pub revoked_at: i64,
+}
+
+#[derive(Accounts)]
+pub struct RevokeStandingDelegation<'info> {
+    #[account(
+        mut,
+        close = o_delegator,
+        constraint = standing_delegation.delegator == o_delegator.key() @ VotingError::UnauthorizedAccess
+    )]
+    pub standing_delegation: Account<'info, StandingDelegation>,
+    #[account(seeds = [b"voting"], bump = voting.bump)]
+    pub voting: Account<'info, Voting>,
+    #[account(mut)]
+    pub o_delegator: Signer<'info>,
+}
+
+// Stops the delegation from being applied to further polls; copies already applied to a poll
+// stay until overridden there.
+pub fn handler(ctx: Context<RevokeStandingDelegation>) -> Result<()> {
+    ensure_operational(&ctx.accounts.voting, None)?;
+
+    let standing_delegation = &ctx.accounts.standing_delegation;
+    let moment = Clock::get()?.unix_timestamp;
+
+    emit!(StandingDelegationRevoked {
+        standing_delegation: standing_delegation.key(),
+        delegator: standing_delegation.delegator,
+        delegatee: standing_delegation.delegatee,
+        revoked_at: moment,
+    });
+
+    msg!("Standing delegation revoked");
+    Ok(())
+}
@@ -0,0 +1,44 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::error::*;
+
+#[event]
+pub struct AllowlistRootUpdated {
+    pub poll: Pubkey,
+    pub previous_root: [u8; 32],
+    pub allowlist_root: [u8; 32],
+    pub updated_at: i64,
+}
+
+#[derive(Accounts)]
+pub struct SetAllowlistRoot<'info> {
+    #[account(mut, constraint = poll.authority == authority.key() @ VotingError::UnauthorizedAccess)]
+    pub poll: Account<'info, Poll>,
+    #[account(seeds = [b"voting"], bump = voting.bump)]
+    pub voting: Account<'info, Voting>,
+    pub authority: Signer<'info>,
+}
+
+// Membership is fixed once ballots can be cast
+pub fn handler(ctx: Context<SetAllowlistRoot>, allowlist_root: [u8; 32]) -> Result<()> {
+    ensure_operational(&ctx.accounts.voting, Some(&ctx.accounts.poll))?;
+
+    let poll = &mut ctx.accounts.poll;
+    let moment = Clock::get()?.unix_timestamp;
+
+    require!(poll.status == PollStatus::Pending && moment < poll.poll_begin_time, VotingError::OperationNotAllowed);
+    let previous_root = poll.allowlist_root.ok_or(VotingError::OperationNotAllowed)?;
+
+    poll.allowlist_root = Some(allowlist_root);
+    poll.updated_at = moment;
+
+    emit!(AllowlistRootUpdated {
+        poll: poll.key(),
+        previous_root,
+        allowlist_root,
+        updated_at: moment,
+    });
+
+    msg!("Allowlist updated for poll {}", poll.poll_name);
+    Ok(())
+}
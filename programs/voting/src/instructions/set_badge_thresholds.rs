@@ -0,0 +1,33 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+
+#[event]
+pub struct BadgeThresholdsUpdated {
+    pub thresholds: BadgeThresholds,
+    pub updated_at: i64,
+}
+
+#[derive(Accounts)]
+pub struct SetBadgeThresholds<'info> {
+    #[account(mut, seeds = [b"voting"], bump = voting.bump)]
+    pub voting: Account<'info, Voting>,
+    pub authority: Signer<'info>,
+}
+
+// Once an admin set is configured this goes through a `SetBadgeThresholds` proposal instead
+pub fn handler(ctx: Context<SetBadgeThresholds>, thresholds: BadgeThresholds) -> Result<()> {
+    ensure_operational(&ctx.accounts.voting, None)?;
+
+    let voting = &mut ctx.accounts.voting;
+    voting.ensure_single_authority(&ctx.accounts.authority.key())?;
+
+    voting.badge_thresholds = thresholds.clone();
+
+    emit!(BadgeThresholdsUpdated {
+        thresholds,
+        updated_at: Clock::get()?.unix_timestamp,
+    });
+
+    msg!("Badge thresholds updated");
+    Ok(())
+}
@@ -0,0 +1,51 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::error::*;
+
+#[event]
+pub struct PollPauseChanged {
+    pub poll: Pubkey,
+    pub authority: Pubkey,
+    pub is_paused: bool,
+    pub reason: String,
+    pub changed_at: i64,
+}
+
+#[derive(Accounts)]
+pub struct SetPollPaused<'info> {
+    #[account(mut, constraint = poll.authority == authority.key() @ VotingError::UnauthorizedAccess)]
+    pub poll: Account<'info, Poll>,
+    #[account(seeds = [b"voting"], bump = voting.bump)]
+    pub voting: Account<'info, Voting>,
+    pub authority: Signer<'info>,
+}
+
+// Pauses or resumes a single poll; only instructions acting on that poll are blocked
+pub fn handler(ctx: Context<SetPollPaused>, is_paused: bool, reason: String) -> Result<()> {
+    // the poll's own pause flag is what this toggles, so only the global pause is checked
+    ensure_operational(&ctx.accounts.voting, None)?;
+
+    let poll = &mut ctx.accounts.poll;
+    let moment = Clock::get()?.unix_timestamp;
+
+    if is_paused {
+        require!(!poll.is_paused, VotingError::AlreadyPaused);
+    } else {
+        require!(poll.is_paused, VotingError::NotPaused);
+    }
+    require!(reason.len() <= 200, VotingError::DataTooLong);
+
+    poll.is_paused = is_paused;
+    poll.updated_at = moment;
+
+    emit!(PollPauseChanged {
+        poll: poll.key(),
+        authority: poll.authority,
+        is_paused,
+        reason,
+        changed_at: moment,
+    });
+
+    msg!("Poll {} {}", poll.poll_name, if is_paused { "paused" } else { "resumed" });
+    Ok(())
+}
@@ -0,0 +1,50 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::error::*;
+
+#[event]
+pub struct WeightSnapshotUpdated {
+    pub poll: Pubkey,
+    pub mint: Pubkey,
+    pub snapshot_slot: u64,
+    pub snapshot_root: [u8; 32],
+    pub updated_at: i64,
+}
+
+#[derive(Accounts)]
+pub struct SetWeightSnapshot<'info> {
+    #[account(mut, constraint = poll.authority == authority.key() @ VotingError::UnauthorizedAccess)]
+    pub poll: Account<'info, Poll>,
+    #[account(seeds = [b"voting"], bump = voting.bump)]
+    pub voting: Account<'info, Voting>,
+    pub authority: Signer<'info>,
+}
+
+// Weights must not change once ballots can be cast
+pub fn handler(ctx: Context<SetWeightSnapshot>, snapshot_slot: u64, snapshot_root: [u8; 32]) -> Result<()> {
+    ensure_operational(&ctx.accounts.voting, Some(&ctx.accounts.poll))?;
+
+    let poll = &mut ctx.accounts.poll;
+    let moment = Clock::get()?.unix_timestamp;
+
+    require!(poll.status == PollStatus::Pending && moment < poll.poll_begin_time, VotingError::OperationNotAllowed);
+    require!(snapshot_slot <= Clock::get()?.slot, VotingError::InvalidWeightProof);
+
+    let token_weight = poll.token_weight.as_mut().ok_or(VotingError::OperationNotAllowed)?;
+    token_weight.snapshot_slot = snapshot_slot;
+    token_weight.snapshot_root = snapshot_root;
+    let mint = token_weight.mint;
+
+    poll.updated_at = moment;
+
+    emit!(WeightSnapshotUpdated {
+        poll: poll.key(),
+        mint,
+        snapshot_slot,
+        snapshot_root,
+        updated_at: moment,
+    });
+
+    msg!("Weight snapshot updated for poll {}", poll.poll_name);
+    Ok(())
+}
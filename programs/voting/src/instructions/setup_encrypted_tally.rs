@@ -0,0 +1,103 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::error::*;
+use crate::utils::*;
+
+#[event]
+pub struct EncryptedTallyConfigured {
+    pub poll: Pubkey,
+    pub election_public_key: [u8; 32],
+    pub trustees: Vec<Pubkey>,
+    pub threshold: u8,
+    pub configured_at: i64,
+}
+
+#[derive(Accounts)]
+pub struct SetupEncryptedTally<'info> {
+    #[account(
+        constraint = poll.authority == authority.key() @ VotingError::UnauthorizedAccess,
+        constraint = poll.encrypted_ballots @ VotingError::OperationNotAllowed
+    )]
+    pub poll: Account<'info, Poll>,
+    #[account(seeds = [b"voting"], bump = voting.bump)]
+    pub voting: Account<'info, Voting>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = EncryptedTally::MAX_SIZE,
+        seeds = [b"encrypted_tally", poll.key().as_ref()],
+        bump
+    )]
+    pub encrypted_tally: Account<'info, EncryptedTally>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// Registers the election key and the trustee committee of an encrypted poll.
+// The key is expected to come from a t-of-n distributed key generation: trustee i (1-based)
+// holds x_i, publishes x_i·G as its key share, and any `threshold` shares interpolate to x·G.
+// All n shares are checked, so whichever trustees submit first, their shares decrypt.
+pub fn handler(
+    ctx: Context<SetupEncryptedTally>,
+    election_public_key: [u8; 32],
+    trustees: Vec<Trustee>,
+    threshold: u8,
+) -> Result<()> {
+    ensure_operational(&ctx.accounts.voting, Some(&ctx.accounts.poll))?;
+
+    let poll = &ctx.accounts.poll;
+    let encrypted_tally = &mut ctx.accounts.encrypted_tally;
+    let moment = Clock::get()?.unix_timestamp;
+
+    require!(moment < poll.poll_begin_time, VotingError::OperationNotAllowed);
+    require!(
+        !trustees.is_empty() && trustees.len() <= MAX_TRUSTEES,
+        VotingError::InvalidTrusteeConfiguration
+    );
+    require!(
+        threshold >= 1 && threshold as usize <= trustees.len(),
+        VotingError::InvalidTrusteeConfiguration
+    );
+    require!(is_valid_point(&election_public_key), VotingError::InvalidTrusteeConfiguration);
+
+    for (position, trustee) in trustees.iter().enumerate() {
+        require!(is_valid_point(&trustee.key_share), VotingError::InvalidTrusteeConfiguration);
+        require!(
+            !trustees[..position].iter().any(|other| other.authority == trustee.authority),
+            VotingError::InvalidTrusteeConfiguration
+        );
+    }
+
+    // every share must be usable, since any `threshold` trustees may end up decrypting
+    let key_shares: Vec<[u8; 32]> = trustees.iter().map(|trustee| trustee.key_share).collect();
+    require!(
+        verify_key_shares(&key_shares, threshold, &election_public_key)?,
+        VotingError::InvalidTrusteeConfiguration
+    );
+
+    encrypted_tally.poll = poll.key();
+    encrypted_tally.election_public_key = election_public_key;
+    encrypted_tally.trustees = trustees.clone();
+    encrypted_tally.threshold = threshold;
+    encrypted_tally.aggregate = vec![Ciphertext::default(); poll.options.len()];
+    encrypted_tally.ballots_aggregated = 0;
+    encrypted_tally.decryption_shares = Vec::new();
+    encrypted_tally.is_published = false;
+    encrypted_tally.published_at = None;
+    encrypted_tally.bump = ctx.bumps.encrypted_tally;
+
+    emit!(EncryptedTallyConfigured {
+        poll: poll.key(),
+        election_public_key,
+        trustees: trustees.iter().map(|trustee| trustee.authority).collect(),
+        threshold,
+        configured_at: moment,
+    });
+
+    msg!("Encrypted tally configured for poll {}: {}-of-{} trustees", poll.poll_name, threshold, trustees.len());
+    Ok(())
+}
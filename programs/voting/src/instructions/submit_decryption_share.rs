@@ -0,0 +1,92 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::error::*;
+use crate::utils::*;
+
+#[event]
+pub struct DecryptionShareSubmitted {
+    pub poll: Pubkey,
+    pub trustee: Pubkey,
+    pub trustee_index: u8,
+    pub shares_submitted: u8,
+    pub threshold: u8,
+    pub submitted_at: i64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct DecryptionShareInput {
+    pub share: [u8; 32], // x_i·c1 of one option's aggregate
+    pub proof: EqualityProof,
+}
+
+#[derive(Accounts)]
+pub struct SubmitDecryptionShare<'info> {
+    #[account(constraint = poll.status == PollStatus::Closed @ VotingError::PollNotClosed)]
+    pub poll: Account<'info, Poll>,
+    #[account(seeds = [b"voting"], bump = voting.bump)]
+    pub voting: Account<'info, Voting>,
+
+    #[account(
+        mut,
+        seeds = [b"encrypted_tally", poll.key().as_ref()],
+        bump = encrypted_tally.bump,
+        constraint = !encrypted_tally.is_published @ VotingError::TallyAlreadyComplete
+    )]
+    pub encrypted_tally: Account<'info, EncryptedTally>,
+
+    pub trustee: Signer<'info>,
+}
+
+// A trustee's partial decryption x_i·c1 of every option's aggregate, each with a proof that it
+// used the same secret as its registered key share (log_G(x_i·G) == log_c1(x_i·c1)).
+pub fn handler(ctx: Context<SubmitDecryptionShare>, shares: Vec<DecryptionShareInput>) -> Result<()> {
+    ensure_operational(&ctx.accounts.voting, Some(&ctx.accounts.poll))?;
+
+    let poll = &ctx.accounts.poll;
+    let encrypted_tally = &mut ctx.accounts.encrypted_tally;
+    let moment = Clock::get()?.unix_timestamp;
+
+    let trustee_index = encrypted_tally
+        .trustee_index(&ctx.accounts.trustee.key())
+        .ok_or(VotingError::UnauthorizedAccess)?;
+    require!(
+        !encrypted_tally.decryption_shares.iter().any(|share| share.trustee_index == trustee_index),
+        VotingError::DecryptionShareAlreadySubmitted
+    );
+    require!(
+        encrypted_tally.decryption_shares.len() < encrypted_tally.threshold as usize,
+        VotingError::DecryptionShareAlreadySubmitted
+    );
+    require!(shares.len() == encrypted_tally.aggregate.len(), VotingError::InvalidDecryptionShare);
+
+    let key_share = encrypted_tally.trustees[trustee_index as usize - 1].key_share;
+    let generator = basepoint_bytes();
+
+    for (option, (share, aggregate)) in shares.iter().zip(encrypted_tally.aggregate.iter()).enumerate() {
+        require!(is_valid_point(&share.share), VotingError::InvalidDecryptionShare);
+
+        let mut context = Vec::with_capacity(32 + 2);
+        context.extend_from_slice(poll.key().as_ref());
+        context.push(trustee_index);
+        context.push(option as u8);
+
+        verify_equality_proof(&generator, &key_share, &aggregate.c1, &share.share, &context, &share.proof)?;
+    }
+
+    encrypted_tally.decryption_shares.push(DecryptionShare {
+        trustee_index,
+        shares: shares.iter().map(|share| share.share).collect(),
+    });
+
+    emit!(DecryptionShareSubmitted {
+        poll: poll.key(),
+        trustee: ctx.accounts.trustee.key(),
+        trustee_index,
+        shares_submitted: encrypted_tally.decryption_shares.len() as u8,
+        threshold: encrypted_tally.threshold,
+        submitted_at: moment,
+    });
+
+    msg!("Decryption share {} submitted for poll {}", trustee_index, poll.poll_name);
+    Ok(())
+}
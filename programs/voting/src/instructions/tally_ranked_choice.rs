@@ -0,0 +1,107 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::error::*;
+
+#[event]
+pub struct RankedChoiceRoundCompleted {
+    pub poll: Pubkey,
+    pub round: u8,
+    pub round_counts: Vec<u64>,
+    pub exhausted_weight: u64,
+    pub eliminated: Option<u8>,
+    pub winner: Option<u8>,
+}
+
+#[derive(Accounts)]
+pub struct TallyRankedChoice<'info> {
+    #[account(constraint = poll.status == PollStatus::Closed @ VotingError::PollNotClosed)]
+    pub poll: Account<'info, Poll>,
+    #[account(seeds = [b"voting"], bump = voting.bump)]
+    pub voting: Account<'info, Voting>,
+
+    #[account(
+        mut,
+        seeds = [b"ranked_tally", poll.key().as_ref()],
+        bump = ranked_choice_tally.bump,
+        constraint = !ranked_choice_tally.is_complete @ VotingError::TallyAlreadyComplete
+    )]
+    pub ranked_choice_tally: Account<'info, RankedChoiceTally>,
+
+    #[account(mut, seeds = [b"poll_result", poll.key().as_ref()], bump = poll_result.bump)]
+    pub poll_result: Account<'info, PollResult>,
+}
+
+// Permissionless: counts the `Vote` accounts passed as writable remaining accounts towards the
+// current instant-runoff round. Ballots already counted in this round are skipped, so batches
+// may overlap; the round closes once every ballot of the poll has been seen.
+pub fn handler<'info>(ctx: Context<'_, '_, 'info, 'info, TallyRankedChoice<'info>>) -> Result<()> {
+    ensure_operational(&ctx.accounts.voting, Some(&ctx.accounts.poll))?;
+
+    let poll = &ctx.accounts.poll;
+    let tally = &mut ctx.accounts.ranked_choice_tally;
+    let moment = Clock::get()?.unix_timestamp;
+
+    for vote_info in ctx.remaining_accounts.iter() {
+        require!(vote_info.is_writable, VotingError::InvalidBallotAccount);
+
+        let mut vote: Account<'info, Vote> = Account::try_from(vote_info)?;
+        require!(vote.poll == poll.key(), VotingError::InvalidBallotAccount);
+
+        // already counted this round, or a sealed ballot that was never revealed
+        if vote.tallied_round == 0 || vote.tallied_round >= tally.round {
+            continue;
+        }
+
+        match tally.next_preference(&vote.ballot) {
+            Some(option) => {
+                tally.round_counts[option as usize] = tally.round_counts[option as usize]
+                    .checked_add(vote.vote_weight)
+                    .ok_or(VotingError::ArithmeticOverflow)?;
+            },
+            None => {
+                tally.exhausted_weight = tally.exhausted_weight
+                    .checked_add(vote.vote_weight)
+                    .ok_or(VotingError::ArithmeticOverflow)?;
+            },
+        }
+
+        tally.ballots_counted = tally.ballots_counted
+            .checked_add(1)
+            .ok_or(VotingError::ArithmeticOverflow)?;
+
+        vote.tallied_round = tally.round;
+        vote.exit(&crate::ID)?;
+    }
+
+    if tally.ballots_counted == tally.total_ballots {
+        let round = tally.round;
+        let round_counts = tally.round_counts.clone();
+        let exhausted_weight = tally.exhausted_weight;
+
+        tally.finish_round(moment);
+
+        if tally.is_complete {
+            ctx.accounts.poll_result.finalize(round_counts.clone(), tally.winner.into_iter().collect(), moment);
+        }
+
+        emit!(RankedChoiceRoundCompleted {
+            poll: poll.key(),
+            round,
+            round_counts,
+            exhausted_weight,
+            eliminated: if tally.is_complete { None } else { tally.elimination_order.last().copied() },
+            winner: tally.winner,
+        });
+
+        msg!("Ranked-choice round {} completed for poll {}", round, poll.poll_name);
+    } else {
+        msg!(
+            "Ranked-choice round {}: {}/{} ballots counted",
+            tally.round,
+            tally.ballots_counted,
+            tally.total_ballots
+        );
+    }
+
+    Ok(())
+}
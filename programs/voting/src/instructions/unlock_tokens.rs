@@ -0,0 +1,72 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Token, TokenAccount};
+use crate::state::*;
+use crate::error::*;
+
+#[event]
+pub struct TokensUnlocked {
+    pub poll: Pubkey,
+    pub owner: Pubkey,
+    pub amount: u64,
+    pub unlocked_at: i64,
+}
+
+#[derive(Accounts)]
+#[instruction(nullifier_hash: [u8; 32])]
+pub struct UnlockTokens<'info> {
+    #[account(constraint = matches!(poll.status, PollStatus::Closed | PollStatus::Cancelled) @ VotingError::TokensStillLocked)]
+    pub poll: Account<'info, Poll>,
+    #[account(
+        mut,
+        seeds = [b"vote", poll.key().as_ref(), &nullifier_hash],
+        bump = vote.bump,
+        constraint = vote.lock_owner == Some(owner.key()) @ VotingError::UnauthorizedAccess
+    )]
+    pub vote: Account<'info, Vote>,
+
+    #[account(mut, seeds = [b"escrow_vault", poll.key().as_ref()], bump)]
+    pub escrow_vault: Account<'info, TokenAccount>,
+    /// CHECK: PDA that only signs for the vault
+    #[account(seeds = [b"escrow_authority", poll.key().as_ref()], bump)]
+    pub escrow_authority: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        constraint = owner_token_account.owner == owner.key() @ VotingError::InvalidEscrowAccount,
+        constraint = owner_token_account.mint == escrow_vault.mint @ VotingError::InvalidEscrowAccount
+    )]
+    pub owner_token_account: Account<'info, TokenAccount>,
+
+    pub owner: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+// The ballot itself is kept, only the lock is released
+pub fn handler(ctx: Context<UnlockTokens>, _nullifier_hash: [u8; 32]) -> Result<()> {
+    // no `ensure_operational`: refunds of locked stake stay available while paused
+    let vote = &mut ctx.accounts.vote;
+    let poll_key = ctx.accounts.poll.key();
+    let amount = vote.release_lock()?;
+
+    token::transfer(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            token::Transfer {
+                from: ctx.accounts.escrow_vault.to_account_info(),
+                to: ctx.accounts.owner_token_account.to_account_info(),
+                authority: ctx.accounts.escrow_authority.to_account_info(),
+            },
+            &[&[b"escrow_authority", poll_key.as_ref(), &[ctx.bumps.escrow_authority]]],
+        ),
+        amount,
+    )?;
+
+    emit!(TokensUnlocked {
+        poll: poll_key,
+        owner: ctx.accounts.owner.key(),
+        amount,
+        unlocked_at: Clock::get()?.unix_timestamp,
+    });
+
+    msg!("Unlocked {} tokens from poll {}", amount, ctx.accounts.poll.poll_name);
+    Ok(())
+}
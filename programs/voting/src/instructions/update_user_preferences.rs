@@ -0,0 +1,44 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::instructions::create_user_preferences::UserPreferencesUpdated;
+
+#[derive(Accounts)]
+pub struct UpdateUserPreferences<'info> {
+    #[account(mut, seeds = [b"user_preferences", owner.key().as_ref()], bump = user_preferences.bump)]
+    pub user_preferences: Account<'info, UserPreferences>,
+    #[account(mut, seeds = [b"user_stats", owner.key().as_ref()], bump = user_stats.bump)]
+    pub user_stats: Account<'info, UserStats>,
+    #[account(seeds = [b"voting"], bump = voting.bump)]
+    pub voting: Account<'info, Voting>,
+    pub owner: Signer<'info>,
+}
+
+pub fn handler(
+    ctx: Context<UpdateUserPreferences>,
+    notification_settings: NotificationSettings,
+    privacy_settings: PrivacySettings,
+    accessibility_settings: AccessibilitySettings,
+) -> Result<()> {
+    ensure_operational(&ctx.accounts.voting, None)?;
+
+    let user_preferences = &mut ctx.accounts.user_preferences;
+    let moment = Clock::get()?.unix_timestamp;
+
+    ctx.accounts.user_stats.set_hide_voting_history(privacy_settings.hide_voting_history);
+
+    user_preferences.version = UserPreferences::CURRENT_VERSION;
+    user_preferences.notification_settings = notification_settings;
+    user_preferences.privacy_settings = privacy_settings;
+    user_preferences.accessibility_settings = accessibility_settings;
+    user_preferences.updated_at = moment;
+
+    emit!(UserPreferencesUpdated {
+        user: user_preferences.user,
+        version: user_preferences.version,
+        hide_voting_history: user_preferences.privacy_settings.hide_voting_history,
+        updated_at: moment,
+    });
+
+    msg!("User preferences updated for {}", user_preferences.user);
+    Ok(())
+}
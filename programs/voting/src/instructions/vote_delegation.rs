@@ -2,6 +2,7 @@ use anchor_lang::prelude::*;
 use crate::state::*;
 use crate::error::*;
 use crate::utils::*;
+use crate::instructions::award_badge::mint_earned_badges;
 
 #[event]
 pub struct VoteDelegationCreated {
@@ -11,45 +12,66 @@ pub struct VoteDelegationCreated {
     created_at : i64,
     expires_at : Option<i64>,
     nullifier_hash : [u8; 32],
+    chain_length : u8,
 }
 
 #[derive(Accounts)]
-#[instruction(nullifier_hash: [u8; 32], delegate_pubkey: Pubkey)]
+#[instruction(zk_proof: Vec<u8>, delegate_pubkey: Pubkey, nullifier_hash: [u8; 32])]
 pub struct DelegateVote<'info> {
     #[account(mut, constraint = poll.is_active() @ VotingError::PollNotActive)]
     pub poll: Account<'info, Poll>,
+    #[account(seeds = [b"voting"], bump = voting.bump)]
+    pub voting: Account<'info, Voting>,
+    #[account(address = poll.verifying_key @ VotingError::InvalidVerifyingKey)]
+    pub verifying_key: Account<'info, VerifyingKey>,
     #[account(init, payer = o_delegator, space = VoteDelegation::MAX_SIZE, seeds = [b"delegation", poll.key().as_ref(), &nullifier_hash], bump)]
     pub vote_delegation: Account<'info, VoteDelegation>,
-    #[account(init, payer = o_delegator, space = VoterNullifier::MAX_SIZE, seeds = [b"nullifier", poll.key().as_ref(), &nullifier_hash], bump)]
-    pub voter_nullifier: Account<'info, VoterNullifier>,
+    #[account(init, payer = o_delegator, space = DelegationRoute::MAX_SIZE, seeds = [b"delegation_route", poll.key().as_ref(), o_delegator.key().as_ref()], bump)]
+    pub delegation_route: Account<'info, DelegationRoute>,
+    // The delegator's nullifier stays unspent so they can still override by voting directly;
+    // it must not have voted already.
+    /// CHECK: only checked to be empty, the address is fixed by the seeds
+    #[account(seeds = [b"nullifier", poll.key().as_ref(), &nullifier_hash], bump)]
+    pub voter_nullifier: UncheckedAccount<'info>,
+    // Optional: credits the delegation to the delegator's profile
+    #[account(mut, seeds = [b"user", o_delegator.key().as_ref()], bump = user.bump)]
+    pub user: Option<Account<'info, User>>,
     #[account(mut)]
     pub o_delegator: Signer<'info>,
     pub o_delegatee: UncheckedAccount<'info>,
     pub system_program: Program<'info, System>,
 }
 
-pub fn handler (
-    ctx: Context<DelegateVote>,
+// The delegatee's own outgoing chain is passed as remaining accounts, see
+// `DelegationRoute::chain_length`; walking it rejects cycles and chains longer than the poll allows.
+// Badge PDAs for `mint_earned_badges` may follow the routes.
+pub fn handler<'info>(
+    ctx: Context<'_, '_, '_, 'info, DelegateVote<'info>>,
     zk_proof: Vec<u8>,
     delegate_pubkey: Pubkey,
     nullifier_hash: [u8; 32],
-    public_signals: Vec<String>,
+    public_signals: Vec<[u8; 32]>,
     expires_at: Option<i64>,
 ) -> Result<()> {
+    ensure_operational(&ctx.accounts.voting, Some(&ctx.accounts.poll))?;
+
     let poll = &mut ctx.accounts.poll;
     let moment = Clock::get()?.unix_timestamp;
     let vote_delegation = &mut ctx.accounts.vote_delegation;
-    let voter_nullifier = &mut ctx.accounts.voter_nullifier;
+    let delegation_route = &mut ctx.accounts.delegation_route;
+    let delegator = ctx.accounts.o_delegator.key();
 
     require!(poll.is_active(), VotingError::PollNotActive);
     require!(moment <= poll.poll_end_time, VotingError::VotingEnded);
+    require!(poll.max_delegation_depth > 0, VotingError::OperationNotAllowed);
+    require!(ctx.accounts.voter_nullifier.data_is_empty(), VotingError::VoterAlreadyVoted);
 
     // delgate and delegeteee validation
-    require!(delegate_pubkey != ctx.accounts.o_delegator.key(), VotingError::CannotDelegateToSelf);
+    require!(delegate_pubkey != delegator, VotingError::CannotDelegateToSelf);
 
     // verification
     require!(!zk_proof.is_empty(), VotingError::InvalidZkProof);
-    require!(verify_zk_proof(&zk_proof, &public_signals, &poll.eligibility_criteria)?, VotingError::ZkProofVerificatiionFailed);
+    require!(verify_zk_proof(&zk_proof, &public_signals, &ctx.accounts.verifying_key.groth16()?, &poll.key(), &nullifier_hash)?, VotingError::ZkProofVerificatiionFailed);
 
 
     if let Some(timeout) = expires_at {
@@ -57,30 +79,53 @@ pub fn handler (
         require!(timeout <= poll.poll_end_time, VotingError::ExpirationAfterVotingEnded);
     }
 
+    let chain_length = DelegationRoute::chain_length(
+        &poll.key(),
+        &delegator,
+        &delegate_pubkey,
+        poll.max_delegation_depth,
+        ctx.remaining_accounts,
+    )?;
+
     // initialisation of the vote_delegation
     vote_delegation.poll = poll.key();
-    vote_delegation.o_delegator = ctx.accounts.o_delegator.key();
+    vote_delegation.o_delegator = delegator;
     vote_delegation.is_active = true;
     vote_delegation.o_delegatee = delegate_pubkey;
     vote_delegation.created_at = moment;
     vote_delegation.expires_at = expires_at;
+    vote_delegation.used_in = None;
     vote_delegation.bump = ctx.bumps.vote_delegation;
 
-    // initialisation of the voter_nullifier
-    voter_nullifier.poll = poll.key();
-    voter_nullifier.nullifier_hash = nullifier_hash;
-    voter_nullifier.bump = ctx.bumps.voter_nullifier;
-    voter_nullifier.created_at = moment;
+    delegation_route.poll = poll.key();
+    delegation_route.delegator = delegator;
+    delegation_route.delegatee = delegate_pubkey;
+    delegation_route.delegation = vote_delegation.key();
+    delegation_route.bump = ctx.bumps.delegation_route;
+
+    if let Some(user) = ctx.accounts.user.as_mut() {
+        user.record_delegation_given()?;
+        // one route PDA is consumed per hop, ending with the empty one
+        mint_earned_badges(
+            &ctx.accounts.voting,
+            user,
+            None,
+            &ctx.remaining_accounts[chain_length as usize..],
+            &ctx.accounts.o_delegator.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            moment,
+        )?;
+    }
 
     emit!(VoteDelegationCreated {
         poll: poll.key(),
-        delegator: ctx.accounts.o_delegator.key(),
+        delegator,
         delegatee: delegate_pubkey,
         created_at: moment,
-        expires_at: expires_at,
-        nullifier_hash: nullifier_hash,
+        expires_at,
+        nullifier_hash,
+        chain_length,
     });
-    msg!("Vote delegated successfully for poll: {}", poll.name);
+    msg!("Vote delegated successfully for poll: {}", poll.poll_name);
     Ok(())
 }
- 
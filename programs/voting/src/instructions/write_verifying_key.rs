@@ -0,0 +1,34 @@
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::error::*;
+
+#[derive(Accounts)]
+pub struct WriteVerifyingKey<'info> {
+    #[account(
+        mut,
+        seeds = [b"verifying_key", authority.key().as_ref(), verifying_key.circuit_id.as_bytes()],
+        bump = verifying_key.bump,
+        constraint = verifying_key.authority == authority.key() @ VotingError::UnauthorizedAccess,
+        constraint = !verifying_key.is_finalized @ VotingError::VerifyingKeyFinalized
+    )]
+    pub verifying_key: Account<'info, VerifyingKey>,
+    #[account(seeds = [b"voting"], bump = voting.bump)]
+    pub voting: Account<'info, Voting>,
+
+    pub authority: Signer<'info>,
+}
+
+pub fn handler(ctx: Context<WriteVerifyingKey>, offset: u32, chunk: Vec<u8>) -> Result<()> {
+    ensure_operational(&ctx.accounts.voting, None)?;
+
+    let verifying_key = &mut ctx.accounts.verifying_key;
+
+    let start = offset as usize;
+    let end = start.checked_add(chunk.len()).ok_or(VotingError::ArithmeticOverflow)?;
+    require!(!chunk.is_empty() && end <= verifying_key.data.len(), VotingError::DataTooLong);
+
+    verifying_key.data[start..end].copy_from_slice(&chunk);
+
+    msg!("Wrote {} bytes at offset {} to verifying key '{}'", chunk.len(), offset, verifying_key.circuit_id);
+    Ok(())
+}
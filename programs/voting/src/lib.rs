@@ -1,5 +1,7 @@
+// Anchor 0.31's generated IDL instructions still call `AccountInfo::realloc`.
+#![allow(deprecated)]
+
 use anchor_lang::prelude::*;
-use anchor_spl::token::{self, Token, TokenAccount, Mint};
 
 pub mod state;
 pub mod instructions;
@@ -8,22 +10,34 @@ pub mod utils;
 
 use instructions::*;
 use state::*;
-use error::*;
+use utils::{ZeroOneProof, EqualityProof};
 
-declare_id!();   // For now it is still empty..
+declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
 
 #[program]
 pub mod globalvote{
-    use std::task::Context;
-
     use super::*;
     pub fn initialize(ctx: Context<Initialize>) -> Result<()> {
         instructions::initialize::handler(ctx)
     }
 
+    // Verifying key upload for an eligibility circuit: create, write in chunks, then finalize
+    pub fn create_verifying_key(ctx: Context<CreateVerifyingKey>, circuit_id: String, public_inputs: u8) -> Result<()> {
+        instructions::create_verifying_key::handler(ctx, circuit_id, public_inputs)
+    }
+
+    pub fn write_verifying_key(ctx: Context<WriteVerifyingKey>, offset: u32, chunk: Vec<u8>) -> Result<()> {
+        instructions::write_verifying_key::handler(ctx, offset, chunk)
+    }
+
+    pub fn finalize_verifying_key(ctx: Context<FinalizeVerifyingKey>) -> Result<()> {
+        instructions::finalize_verifying_key::handler(ctx)
+    }
+
     // Creation of a new poll
-    pub fn create_poll(
-        ctx: Context<CreatePoll>,
+    #[allow(clippy::too_many_arguments)]
+    pub fn create_poll<'info>(
+        ctx: Context<'_, '_, '_, 'info, CreatePoll<'info>>,
         poll_name: String,
         options: Vec<String>,
         description: String,
@@ -36,51 +50,192 @@ pub mod globalvote{
         quadratic_parameters: Option<QuadraticParameters>,
         allow_recast: bool,
         recast_vote_window: Option<i64>,
+        approval_parameters: Option<ApprovalParameters>,
+        reveal_window: Option<i64>,
+        encrypted_ballots: bool,
+        max_delegation_depth: u8,
+        tags: Vec<String>,
+        access: PollAccessConfig,
     ) -> Result <()> {
-        instructions::create_poll::handler(ctx, poll_name, options, description, eligibility_criteria, poll_begin_time, poll_end_time, max_voters, poll_type, geolocation_required, quadratic_parameters, allow_recast, recast_vote_window)
+        instructions::create_poll::handler(ctx, poll_name, options, description, eligibility_criteria, poll_begin_time, poll_end_time, max_voters, poll_type, geolocation_required, quadratic_parameters, allow_recast, recast_vote_window, approval_parameters, reveal_window, encrypted_ballots, max_delegation_depth, tags, access)
+    }
+
+    // Opens a pending poll once its begin time has passed
+    pub fn activate_poll(ctx: Context<ActivatePoll>) -> Result<()> {
+        instructions::activate_poll::handler(ctx)
+    }
+
+    pub fn cancel_poll(ctx: Context<CancelPoll>, reason: String) -> Result<()> {
+        instructions::cancel_poll::handler(ctx, reason)
     }
 
     // This allows to cast a vote with Zk proof
-    pub fn cast_vote(
-        ctx: Context<CastVote>, 
-        vote_choice: u8, 
+    pub fn cast_vote<'info>(
+        ctx: Context<'_, '_, '_, 'info, CastVote<'info>>,
+        ballot: Ballot, 
         zk_proof: Vec<u8>, 
         nullifier_hash: [u8; 32], 
-        public_signals: Vec<String>, 
-        vote_weight: Option<u64>,) -> Result <()> {
-        instructions::cast_vote::handler(ctx, vote_choice, zk_proof, nullifier_hash, public_signals, vote_weight)
+        public_signals: Vec<[u8; 32]>, 
+        extras: VoteExtras,) -> Result <()> {
+        instructions::cast_vote::handler(ctx, ballot, zk_proof, nullifier_hash, public_signals, extras)
     }
-        pub fn recast_vote(ctx: Context<RecastVote>, new_vote_choice: u8, zk_proof: Vec<u8>, nullifier_hash: [u8; 32], public_signals: Vec<String>, new_vote_weight: Option<u64>,) -> Result <()> {
-            instructions::recast_vote::handler(ctx, new_vote_choice, zk_proof, nullifier_hash, public_signals, new_vote_weight,)
+        pub fn recast_vote(ctx: Context<RecastVote>, new_ballot: Ballot, zk_proof: Vec<u8>, nullifier_hash: [u8; 32], public_signals: Vec<[u8; 32]>,) -> Result <()> {
+            instructions::recast_vote::handler(ctx, new_ballot, zk_proof, nullifier_hash, public_signals,)
         }
+        // Opens a sealed ballot on a commit-reveal poll
+        pub fn reveal_vote(ctx: Context<RevealVote>, ballot: Ballot, salt: [u8; 32], nullifier_hash: [u8; 32]) -> Result<()> {
+            instructions::reveal_vote::handler(ctx, ballot, salt, nullifier_hash)
+        }
+
+        // Encrypted polls: register trustees, prove and aggregate ballots, then decrypt at close
+        pub fn setup_encrypted_tally(ctx: Context<SetupEncryptedTally>, election_public_key: [u8; 32], trustees: Vec<Trustee>, threshold: u8) -> Result<()> {
+            instructions::setup_encrypted_tally::handler(ctx, election_public_key, trustees, threshold)
+        }
+
+        pub fn prove_encrypted_ballot(ctx: Context<ProveEncryptedBallot>, nullifier_hash: [u8; 32], proofs: Vec<ZeroOneProof>, sum_proof: EqualityProof) -> Result<()> {
+            instructions::prove_encrypted_ballot::handler(ctx, nullifier_hash, proofs, sum_proof)
+        }
+
+        pub fn submit_decryption_share(ctx: Context<SubmitDecryptionShare>, shares: Vec<DecryptionShareInput>) -> Result<()> {
+            instructions::submit_decryption_share::handler(ctx, shares)
+        }
+
+        pub fn publish_tally(ctx: Context<PublishTally>, totals: Vec<u64>) -> Result<()> {
+            instructions::publish_tally::handler(ctx, totals)
+        }
+
         pub fn close_poll(ctx: Context<ClosePoll>) -> Result<()> {
             instructions::close_poll::handler(ctx)
         }
 
-        pub fn award_badge(ctx: Context<AwardBadge>, badge_type: BadgeType, recipient: Pubkey, data: String) -> Result<()> {
-            instructions::award_badge::handler(ctx, badge_type, recipient, data)
+        // Refunds the tokens a ballot locked once its poll is closed or cancelled
+        pub fn unlock_tokens(ctx: Context<UnlockTokens>, nullifier_hash: [u8; 32]) -> Result<()> {
+            instructions::unlock_tokens::handler(ctx, nullifier_hash)
         }
 
-        pub fn delegate_vote(ctx: Context<DelegateVote>, zk_proof: Vec<u8>, delegate_pubkey: Pubkey, nullifier_hash: [u8; 32], public_signals: Vec<String>, expires_at: Option<i64>,) -> Result<()> {
+        // Counts a batch of ranked ballots (passed as remaining accounts) for the current runoff round
+        pub fn tally_ranked_choice<'info>(ctx: Context<'_, '_, 'info, 'info, TallyRankedChoice<'info>>) -> Result<()> {
+            instructions::tally_ranked_choice::handler(ctx)
+        }
+
+        // Badges can optionally be minted as a soulbound Token-2022 token with metadata
+        pub fn award_badge(ctx: Context<AwardBadge>, badge_type: BadgeType, recipient: Pubkey, data: String, token_uri: Option<String>) -> Result<()> {
+            instructions::award_badge::handler(ctx, badge_type, recipient, data, token_uri)
+        }
+
+        pub fn revoke_badge(ctx: Context<RevokeBadge>, badge_type: BadgeType, recipient: Pubkey) -> Result<()> {
+            instructions::revoke_badge::handler(ctx, badge_type, recipient)
+        }
+
+        // Activity needed for the badges awarded automatically by cast_vote, create_poll and delegate_vote
+        pub fn set_badge_thresholds(ctx: Context<SetBadgeThresholds>, thresholds: BadgeThresholds) -> Result<()> {
+            instructions::set_badge_thresholds::handler(ctx, thresholds)
+        }
+
+        pub fn delegate_vote<'info>(ctx: Context<'_, '_, '_, 'info, DelegateVote<'info>>, zk_proof: Vec<u8>, delegate_pubkey: Pubkey, nullifier_hash: [u8; 32], public_signals: Vec<[u8; 32]>, expires_at: Option<i64>,) -> Result<()> {
             instructions::vote_delegation::handler(ctx, zk_proof, delegate_pubkey, nullifier_hash, public_signals, expires_at,)
         }
 
+        pub fn revoke_delegation(ctx: Context<RevokeDelegation>, zk_proof: Vec<u8>, nullifier_hash: [u8; 32], public_signals: Vec<[u8; 32]>) -> Result<()> {
+            instructions::revoke_delegation::handler(ctx, zk_proof, nullifier_hash, public_signals)
+        }
+
+        pub fn redelegate(ctx: Context<Redelegate>, zk_proof: Vec<u8>, nullifier_hash: [u8; 32], public_signals: Vec<[u8; 32]>, new_delegatee: Pubkey, expires_at: Option<i64>) -> Result<()> {
+            instructions::redelegate::handler(ctx, zk_proof, nullifier_hash, public_signals, new_delegatee, expires_at)
+        }
+
+        // Standing delegations apply across polls whose tags match
+        pub fn create_standing_delegation(ctx: Context<CreateStandingDelegation>, zk_proof: Vec<u8>, nullifier_hash: [u8; 32], public_signals: Vec<[u8; 32]>, delegatee: Pubkey, tags: Vec<String>, expires_at: Option<i64>) -> Result<()> {
+            instructions::create_standing_delegation::handler(ctx, zk_proof, nullifier_hash, public_signals, delegatee, tags, expires_at)
+        }
+
+        pub fn revoke_standing_delegation(ctx: Context<RevokeStandingDelegation>) -> Result<()> {
+            instructions::revoke_standing_delegation::handler(ctx)
+        }
+
+        pub fn apply_standing_delegation(ctx: Context<ApplyStandingDelegation>, zk_proof: Vec<u8>, nullifier_hash: [u8; 32]) -> Result<()> {
+            instructions::apply_standing_delegation::handler(ctx, zk_proof, nullifier_hash)
+        }
+
+        pub fn override_standing_delegation(ctx: Context<OverrideStandingDelegation>, nullifier_hash: [u8; 32]) -> Result<()> {
+            instructions::override_standing_delegation::handler(ctx, nullifier_hash)
+        }
+
         pub fn initialize_user(ctx: Context<InitializeUser>, did_identifier: Option<String>, location_hash: Option<[u8; 32]>, preferred_language: Option<String>,) -> Result<()> {
             instructions::initialize_user::handler(ctx, did_identifier, location_hash, preferred_language,)
         }
 
-        pub fn emergency_pause(ctx: Context<EmergencyPause>) -> Result<()> {
-            instructions::emergency_pause::handler(ctx)
+        // M-of-N governance over the global config
+        pub fn configure_governance(ctx: Context<ConfigureGovernance>, admins: Vec<Pubkey>, threshold: u8, timelock: i64) -> Result<()> {
+            instructions::configure_governance::handler(ctx, admins, threshold, timelock)
         }
 
-        pub fn resume_operations(ctx: Context<ResumeOperations>) -> Result<()> {
-            instructions::resume_operations::handler(ctx)
+        pub fn create_proposal(ctx: Context<CreateProposal>, action: GovernanceAction) -> Result<()> {
+            instructions::create_proposal::handler(ctx, action)
         }
 
-        pub fn cast_delegated_vote(ctx: Context<CastDelegatedVote>, vote_choice: u8, vote_weight: Option<u64>, zk_proof: Vec<u8>, public_signals: Vec<String>,  delegator_nullifier: [u8; 32], delegate_nullifier: [u8; 32], ) -> Result <()> {
-            instructions::cast_delegated_vote::handler(ctx, vote_choice, zk_proof, delegator_nullifier, delegate_nullifier, public_signals, vote_weight, )
+        pub fn approve_proposal(ctx: Context<ApproveProposal>) -> Result<()> {
+            instructions::approve_proposal::handler(ctx)
         }
 
-        pub fn update_user_preferences(ctx: Context<UpdateUserPreferences>, notification_settings: NotificationSettings, privacy_settings: PrivacySettings, accessibility_settings: AccessibilitySettings) -> Result <()> { instructions::update_user_preferences::handler(ctx, notification_settings, privacy_settings, accessibility_settings)
-    }
+        pub fn execute_proposal(ctx: Context<ExecuteProposal>) -> Result<()> {
+            instructions::execute_proposal::handler(ctx)
+        }
+
+        // Two-step authority transfers for the global config and for polls
+        pub fn propose_authority(ctx: Context<ProposeAuthority>, new_authority: Option<Pubkey>) -> Result<()> {
+            instructions::propose_authority::handler(ctx, new_authority)
+        }
+
+        pub fn accept_authority(ctx: Context<AcceptAuthority>) -> Result<()> {
+            instructions::accept_authority::handler(ctx)
+        }
+
+        pub fn propose_poll_authority(ctx: Context<ProposePollAuthority>, new_authority: Option<Pubkey>) -> Result<()> {
+            instructions::propose_poll_authority::handler(ctx, new_authority)
+        }
+
+        pub fn accept_poll_authority(ctx: Context<AcceptPollAuthority>) -> Result<()> {
+            instructions::accept_poll_authority::handler(ctx)
+        }
+
+        pub fn emergency_pause(ctx: Context<EmergencyPause>, reason: String) -> Result<()> {
+            instructions::emergency_pause::handler(ctx, reason)
+        }
+
+        pub fn resume_operations(ctx: Context<ResumeOperations>, reason: String) -> Result<()> {
+            instructions::resume_operations::handler(ctx, reason)
+        }
+
+        // Replaces the allowlist of a membership poll, until the poll begins
+        pub fn set_allowlist_root(ctx: Context<SetAllowlistRoot>, allowlist_root: [u8; 32]) -> Result<()> {
+            instructions::set_allowlist_root::handler(ctx, allowlist_root)
+        }
+
+        // Publishes the balance snapshot of a token-weighted poll, until the poll begins
+        pub fn set_weight_snapshot(ctx: Context<SetWeightSnapshot>, snapshot_slot: u64, snapshot_root: [u8; 32]) -> Result<()> {
+            instructions::set_weight_snapshot::handler(ctx, snapshot_slot, snapshot_root)
+        }
+
+        // Per-poll pause, on top of the global one
+        pub fn set_poll_paused(ctx: Context<SetPollPaused>, is_paused: bool, reason: String) -> Result<()> {
+            instructions::set_poll_paused::handler(ctx, is_paused, reason)
+        }
+
+        // Casts the delegatee's ballot with the weight of the delegations passed as remaining accounts
+        pub fn cast_delegated_vote<'info>(ctx: Context<'_, '_, 'info, 'info, CastDelegatedVote<'info>>, ballot: Ballot, zk_proof: Vec<u8>, delegate_nullifier: [u8; 32], public_signals: Vec<[u8; 32]>) -> Result <()> {
+            instructions::cast_delegated_vote::handler(ctx, ballot, zk_proof, delegate_nullifier, public_signals)
+        }
+
+        pub fn create_user_preferences(ctx: Context<CreateUserPreferences>, notification_settings: NotificationSettings, privacy_settings: PrivacySettings, accessibility_settings: AccessibilitySettings) -> Result <()> {
+            instructions::create_user_preferences::handler(ctx, notification_settings, privacy_settings, accessibility_settings)
+        }
+
+        pub fn update_user_preferences(ctx: Context<UpdateUserPreferences>, notification_settings: NotificationSettings, privacy_settings: PrivacySettings, accessibility_settings: AccessibilitySettings) -> Result <()> {
+            instructions::update_user_preferences::handler(ctx, notification_settings, privacy_settings, accessibility_settings)
+        }
+
+        pub fn close_user_preferences(ctx: Context<CloseUserPreferences>) -> Result<()> {
+            instructions::close_user_preferences::handler(ctx)
+        }
 }
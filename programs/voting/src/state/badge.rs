@@ -1,5 +1,8 @@
 use anchor_lang::prelude::*;
-use super::BadgeType;
+use super::{BadgeType, User, UserStats};
+
+pub const MAX_BADGE_URI_LENGTH: usize = 200;
+pub const BADGE_TOKEN_SYMBOL: &str = "GVBADGE";
 
 #[account]
 pub struct UserBadge {
@@ -9,6 +12,7 @@ pub struct UserBadge {
     pub awarded_at: i64,
     pub is_active: bool,
     pub bump: u8,
+    pub mint: Option<Pubkey>, // soulbound Token-2022 mint held by the recipient, if one was minted
 }
 
 impl UserBadge {
@@ -18,5 +22,61 @@ impl UserBadge {
         32 + // recipient
         8 + // awarded_at
         1 + // is_active
-        1; // bump
-}
\ No newline at end of file
+        1 + // bump
+        1 + 32; // mint
+
+    pub fn token_name(badge_type: BadgeType) -> String {
+        format!("GlobalVote {:?}", badge_type)
+    }
+
+    // Bytes the Token-2022 metadata extension adds to the mint once initialized: TLV header,
+    // update authority, mint, the three strings and an empty additional metadata list
+    pub fn token_metadata_len(name: &str, uri: &str) -> usize {
+        4 + 32 + 32 + (4 + name.len()) + (4 + BADGE_TOKEN_SYMBOL.len()) + (4 + uri.len()) + 4
+    }
+}
+
+// Activity a profile needs before `mint_earned_badges` awards a badge; 0 disables the rule.
+// `FirstVote` only needs one vote and `Auditor` is only ever awarded by hand.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub struct BadgeThresholds {
+    pub frequent_voter_votes: u64,
+    pub poll_creator_polls: u64,
+    pub delegate_delegations: u64,
+    pub civic_leader_streak: u32, // consecutive days with a vote, see `UserStats.streak_count`
+}
+
+impl BadgeThresholds {
+    pub const MAX_SIZE: usize = 8 + 8 + 8 + 4;
+
+    pub const DEFAULT: BadgeThresholds = BadgeThresholds {
+        frequent_voter_votes: 10,
+        poll_creator_polls: 1,
+        delegate_delegations: 1,
+        civic_leader_streak: 7,
+    };
+
+    // Badges the profile currently qualifies for, whether or not they were already awarded
+    pub fn earned(&self, user: &User, stats: Option<&UserStats>) -> Vec<BadgeType> {
+        let reached = |count: u64, threshold: u64| threshold > 0 && count >= threshold;
+        let mut earned = Vec::new();
+        if user.total_votes_cast > 0 {
+            earned.push(BadgeType::FirstVote);
+        }
+        if reached(user.total_votes_cast, self.frequent_voter_votes) {
+            earned.push(BadgeType::FrequentVoter);
+        }
+        if reached(user.total_polls_created, self.poll_creator_polls) {
+            earned.push(BadgeType::PollCreator);
+        }
+        if reached(user.total_delegations_given, self.delegate_delegations) {
+            earned.push(BadgeType::Delegate);
+        }
+        if let Some(stats) = stats {
+            if reached(stats.streak_count as u64, self.civic_leader_streak as u64) {
+                earned.push(BadgeType::CivicLeader);
+            }
+        }
+        earned
+    }
+}
@@ -0,0 +1,52 @@
+use anchor_lang::prelude::*;
+use crate::utils::{Ciphertext, MAX_ENCRYPTED_OPTIONS, MAX_TRUSTEES};
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct Trustee {
+    pub authority: Pubkey,
+    pub key_share: [u8; 32], // x_i·G, trustee i holds share x_i of the election secret key
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct DecryptionShare {
+    pub trustee_index: u8, // 1-based position in `EncryptedTally.trustees`
+    pub shares: Vec<[u8; 32]>, // x_i·c1 for every option's aggregate ciphertext
+}
+
+// Homomorphic tally of an encrypted poll. Proven ballots are added to `aggregate` while the poll
+// is open; after close, `threshold` trustees post decryption shares and anyone can publish the
+// totals they decrypt to.
+#[account]
+pub struct EncryptedTally {
+    pub poll: Pubkey,
+    pub election_public_key: [u8; 32],
+    pub trustees: Vec<Trustee>,
+    pub threshold: u8,
+    pub aggregate: Vec<Ciphertext>,
+    pub ballots_aggregated: u64,
+    pub decryption_shares: Vec<DecryptionShare>,
+    pub is_published: bool,
+    pub published_at: Option<i64>,
+    pub bump: u8,
+}
+
+impl EncryptedTally {
+    pub const MAX_SIZE: usize = 8 +
+        32 + // poll
+        32 + // election_public_key
+        4 + (MAX_TRUSTEES * (32 + 32)) + // trustees
+        1 + // threshold
+        4 + (MAX_ENCRYPTED_OPTIONS * 64) + // aggregate
+        8 + // ballots_aggregated
+        4 + (MAX_TRUSTEES * (1 + 4 + (MAX_ENCRYPTED_OPTIONS * 32))) + // decryption_shares
+        1 + // is_published
+        1 + 8 + // published_at
+        1; // bump
+
+    pub fn trustee_index(&self, authority: &Pubkey) -> Option<u8> {
+        self.trustees
+            .iter()
+            .position(|trustee| trustee.authority == *authority)
+            .map(|position| position as u8 + 1)
+    }
+}
@@ -0,0 +1,228 @@
+use anchor_lang::prelude::*;
+use crate::error::*;
+use super::{BadgeThresholds, BadgeType, Voting, MAX_BADGE_URI_LENGTH};
+
+pub const MAX_ADMINS: usize = 10;
+pub const MAX_GOVERNANCE_REASON: usize = 200;
+
+// Sensitive actions on the global `Voting` config. Once an admin set is configured they can only
+// be carried out by a proposal approved by `threshold` admins, after the timelock has passed.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub enum GovernanceAction {
+    Pause { reason: String },
+    Resume { reason: String },
+    SetAuthority { authority: Pubkey }, // proposes the new authority, who still has to accept
+    AwardBadge { badge_type: BadgeType, recipient: Pubkey, data: String, token_uri: Option<String> },
+    RevokeBadge { badge_type: BadgeType, recipient: Pubkey },
+    SetPollCreationFee { fee: u64 },
+    SetBadgeThresholds { thresholds: BadgeThresholds },
+    UpdateGovernance { admins: Vec<Pubkey>, threshold: u8, timelock: i64 },
+}
+
+impl GovernanceAction {
+    // AwardBadge with a token URI is the largest
+    pub const MAX_SIZE: usize = 1 + 1 + 32 + (4 + 200) + (1 + 4 + MAX_BADGE_URI_LENGTH);
+
+    // Pausing is the emergency brake and skips the timelock; it still needs the approvals
+    pub fn is_timelocked(&self) -> bool {
+        !matches!(self, GovernanceAction::Pause { .. })
+    }
+}
+
+#[account]
+pub struct GovernanceProposal {
+    pub id: u64,
+    pub proposer: Pubkey,
+    pub action: GovernanceAction,
+    pub governance_version: u32, // admin set the approvals refer to
+    pub approvals: u16, // bitmask over `Voting.admins`
+    pub created_at: i64,
+    pub approved_at: Option<i64>, // when the threshold was reached
+    pub executed_at: Option<i64>,
+    pub bump: u8,
+}
+
+impl GovernanceProposal {
+    pub const MAX_SIZE: usize = 8 +
+        8 + // id
+        32 + // proposer
+        GovernanceAction::MAX_SIZE + // action
+        4 + // governance_version
+        2 + // approvals
+        8 + // created_at
+        1 + 8 + // approved_at
+        1 + 8 + // executed_at
+        1; // bump
+
+    pub fn approval_count(&self) -> u8 {
+        self.approvals.count_ones() as u8
+    }
+
+    // Records an admin's approval; returns true when it brings the proposal to the threshold
+    pub fn approve(&mut self, voting: &Voting, admin: &Pubkey, moment: i64) -> Result<bool> {
+        require!(self.governance_version == voting.governance_version, VotingError::ProposalStale);
+        require!(self.executed_at.is_none(), VotingError::ProposalAlreadyExecuted);
+
+        let index = voting.admin_index(admin).ok_or(VotingError::NotAnAdmin)?;
+        require!(self.approvals & (1 << index) == 0, VotingError::ProposalAlreadyApproved);
+        self.approvals |= 1 << index;
+
+        if self.approved_at.is_none() && self.approval_count() >= voting.threshold {
+            self.approved_at = Some(moment);
+            return Ok(true);
+        }
+        Ok(false)
+    }
+
+    // Marks an approved proposal as executed once its timelock has passed
+    pub fn execute(&mut self, voting: &Voting, moment: i64) -> Result<()> {
+        require!(self.governance_version == voting.governance_version, VotingError::ProposalStale);
+        require!(self.executed_at.is_none(), VotingError::ProposalAlreadyExecuted);
+
+        let executable_at = self.executable_at(voting.timelock).ok_or(VotingError::ProposalNotExecutable)?;
+        require!(moment >= executable_at, VotingError::ProposalNotExecutable);
+
+        self.executed_at = Some(moment);
+        Ok(())
+    }
+
+    pub fn executable_at(&self, timelock: i64) -> Option<i64> {
+        self.approved_at.map(|approved_at| {
+            if self.action.is_timelocked() { approved_at.saturating_add(timelock) } else { approved_at }
+        })
+    }
+}
+
+pub fn validate_admin_set(admins: &[Pubkey], threshold: u8, timelock: i64) -> bool {
+    let distinct = admins.iter().enumerate().all(|(position, admin)| !admins[..position].contains(admin));
+    !admins.is_empty()
+        && admins.len() <= MAX_ADMINS
+        && distinct
+        && threshold >= 1
+        && threshold as usize <= admins.len()
+        && timelock >= 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn voting(admins: Vec<Pubkey>, threshold: u8, timelock: i64) -> Voting {
+        Voting {
+            authority: Pubkey::new_unique(),
+            pending_authority: None,
+            bump: 255,
+            created_at: 0,
+            is_paused: false,
+            paused_at: None,
+            total_polls: 0,
+            total_votes: 0,
+            total_users: 0,
+            admins,
+            threshold,
+            timelock,
+            governance_version: 1,
+            proposal_count: 0,
+            poll_creation_fee: 0,
+            badge_thresholds: BadgeThresholds::DEFAULT,
+        }
+    }
+
+    fn proposal(action: GovernanceAction) -> GovernanceProposal {
+        GovernanceProposal {
+            id: 0,
+            proposer: Pubkey::new_unique(),
+            action,
+            governance_version: 1,
+            approvals: 0,
+            created_at: 0,
+            approved_at: None,
+            executed_at: None,
+            bump: 255,
+        }
+    }
+
+    fn fee_change() -> GovernanceAction {
+        GovernanceAction::SetPollCreationFee { fee: 1 }
+    }
+
+    #[test]
+    fn approval_reaches_threshold_once() {
+        let admins = vec![Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique()];
+        let voting = voting(admins.clone(), 2, 100);
+        let mut proposal = proposal(fee_change());
+
+        assert!(!proposal.approve(&voting, &admins[0], 10).unwrap());
+        assert!(proposal.approve(&voting, &admins[2], 20).unwrap());
+        assert!(!proposal.approve(&voting, &admins[1], 30).unwrap());
+
+        assert_eq!(proposal.approval_count(), 3);
+        assert_eq!(proposal.approved_at, Some(20));
+    }
+
+    #[test]
+    fn rejects_duplicate_foreign_and_stale_approvals() {
+        let admins = vec![Pubkey::new_unique(), Pubkey::new_unique()];
+        let mut voting = voting(admins.clone(), 2, 0);
+        let mut proposal = proposal(fee_change());
+
+        proposal.approve(&voting, &admins[0], 0).unwrap();
+        assert_eq!(
+            proposal.approve(&voting, &admins[0], 0).unwrap_err(),
+            error!(VotingError::ProposalAlreadyApproved)
+        );
+        assert_eq!(
+            proposal.approve(&voting, &Pubkey::new_unique(), 0).unwrap_err(),
+            error!(VotingError::NotAnAdmin)
+        );
+
+        // approvals given under a previous admin set do not carry over
+        voting.governance_version += 1;
+        assert_eq!(proposal.approve(&voting, &admins[1], 0).unwrap_err(), error!(VotingError::ProposalStale));
+    }
+
+    #[test]
+    fn execution_waits_for_approval_and_timelock() {
+        let admins = vec![Pubkey::new_unique(), Pubkey::new_unique()];
+        let voting = voting(admins.clone(), 2, 100);
+        let mut proposal = proposal(fee_change());
+
+        proposal.approve(&voting, &admins[0], 10).unwrap();
+        assert_eq!(proposal.execute(&voting, 1_000).unwrap_err(), error!(VotingError::ProposalNotExecutable));
+
+        proposal.approve(&voting, &admins[1], 10).unwrap();
+        assert_eq!(proposal.executable_at(voting.timelock), Some(110));
+        assert_eq!(proposal.execute(&voting, 109).unwrap_err(), error!(VotingError::ProposalNotExecutable));
+
+        proposal.execute(&voting, 110).unwrap();
+        assert_eq!(proposal.execute(&voting, 111).unwrap_err(), error!(VotingError::ProposalAlreadyExecuted));
+    }
+
+    #[test]
+    fn pause_skips_the_timelock_but_not_the_threshold() {
+        let admins = vec![Pubkey::new_unique(), Pubkey::new_unique()];
+        let voting = voting(admins.clone(), 2, 100);
+        let mut proposal = proposal(GovernanceAction::Pause { reason: String::from("incident") });
+
+        proposal.approve(&voting, &admins[0], 10).unwrap();
+        assert!(proposal.execute(&voting, 10).is_err());
+
+        proposal.approve(&voting, &admins[1], 10).unwrap();
+        proposal.execute(&voting, 10).unwrap();
+    }
+
+    #[test]
+    fn validates_admin_sets() {
+        let admins = vec![Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique()];
+
+        assert!(validate_admin_set(&admins, 1, 0));
+        assert!(validate_admin_set(&admins, 3, 86_400));
+        assert!(!validate_admin_set(&admins, 0, 0));
+        assert!(!validate_admin_set(&admins, 4, 0));
+        assert!(!validate_admin_set(&admins, 2, -1));
+        assert!(!validate_admin_set(&[], 1, 0));
+        assert!(!validate_admin_set(&[admins[0], admins[1], admins[0]], 2, 0));
+        let too_many: Vec<Pubkey> = (0..=MAX_ADMINS).map(|_| Pubkey::new_unique()).collect();
+        assert!(!validate_admin_set(&too_many, 1, 0));
+    }
+}
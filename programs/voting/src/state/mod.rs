@@ -5,12 +5,24 @@ pub mod poll;
 pub mod user;
 pub mod vote;
 pub mod voting;
+pub mod verifying_key;
+pub mod ranked_choice;
+pub mod encrypted_tally;
+pub mod poll_result;
+pub mod standing_delegation;
+pub mod governance;
 
 pub use badge::*;
 pub use poll::*;
 pub use user::*;
 pub use vote::*;
 pub use voting::*;
+pub use verifying_key::*;
+pub use ranked_choice::*;
+pub use encrypted_tally::*;
+pub use poll_result::*;
+pub use standing_delegation::*;
+pub use governance::*;
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
 pub enum PollType{
@@ -19,6 +31,7 @@ pub enum PollType{
     YesNo,
     AnonymousFeedback,
     Quadratic,
+    TokenWeighted, // single choice, weighted by the voter's balance in `Poll.token_weight`
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone)]
@@ -30,7 +43,7 @@ pub struct EligibilityCriteria {
     pub require_custom_criteria : bool
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
 pub enum BadgeType {
     FirstVote,
     Auditor,
@@ -40,7 +53,7 @@ pub enum BadgeType {
     PollCreator,
 }
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
 pub enum PollStatus {
     Pending,
     Active,
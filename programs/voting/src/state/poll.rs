@@ -1,20 +1,29 @@
 use anchor_lang::prelude::*;
-use super::{PollType, EligibilityCriteria, PollStatus};
+use anchor_spl::token_2022::spl_token_2022::{
+    self,
+    extension::{BaseStateWithExtensions, StateWithExtensions},
+    state::{Account as TokenAccountState, Mint as MintState},
+};
+use spl_token_group_interface::state::TokenGroupMember;
+use crate::error::*;
+use crate::utils::{accumulate_nullifier, allowlist_nullifier, member_leaf, verify_merkle_proof, weight_leaf};
+use super::{Ballot, PollType, EligibilityCriteria, PollStatus, MAX_POLL_TAGS, MAX_TAG_LENGTH};
 
 #[account]
 pub struct Poll {
     pub authority: Pubkey,
+    pub pending_authority: Option<Pubkey>, // must accept before becoming `authority`
     pub poll_name: String,
     pub options: Vec<String>,
     pub description: String,
     pub eligibility_criteria: EligibilityCriteria,
+    pub verifying_key: Pubkey,
     pub status: PollStatus,
     pub poll_type: PollType,
     pub poll_begin_time: i64,
     pub poll_end_time: i64,
     pub max_voters: Option<u64>,
     pub total_votes: u64,
-    pub nullifier_hashes: Vec<[u8; 32]>,
     pub vote_counts: Vec<u64>,
     pub recast_vote_window: Option<i64>,
     pub unique_voters: u64,
@@ -22,15 +31,30 @@ pub struct Poll {
     pub updated_at: i64,
     pub allow_recast: bool,
     pub geolocation_required: bool,
-    pub quadratic_parameters: Option<(QuadraticParameters)>,
+    pub quadratic_parameters: Option<QuadraticParameters>,
+    pub approval_parameters: Option<ApprovalParameters>,
+    pub reveal_window: Option<i64>, // commit-reveal mode: seconds after poll_end_time for reveals
+    pub revealed_votes: u64, // sealed ballots opened for counting (revealed or proven)
+    pub encrypted_ballots: bool,
+    pub max_delegation_depth: u8, // longest delegation chain counted towards a ballot, 0 disables delegation
+    pub tags: Vec<String>, // topics matched against standing delegations
+    pub is_paused: bool,
     pub bump: u8,
+    pub token_gate: Option<TokenGate>, // voters must hold this token, see `TokenGate::verify`
+    pub token_weight: Option<TokenWeight>, // balance snapshot on `PollType::TokenWeighted` polls
+    pub token_escrow: Option<TokenEscrow>, // tokens every ballot locks until the poll ends
+    pub allowlist_root: Option<[u8; 32]>, // Merkle root of eligible members, replaces ZK proofs
 }
 
-#[account] 
+// Nullifiers spent in a poll. Uniqueness is enforced by the `VoterNullifier` PDAs; this only
+// keeps a running count and a hash-chain accumulator over them in spend order, so an indexer
+// that enumerates the `VoterNullifier` accounts (or the events) can prove its set is complete.
+#[account]
 pub struct PollMetadata {
     pub poll: Pubkey,
     pub bump: u8,
     pub nullifier_count: u64,
+    pub nullifier_accumulator: [u8; 32],
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone)]
@@ -39,26 +63,251 @@ pub struct QuadraticParameters {
     pub max_credits: u64,
 }
 
+// Selection limits for approval ballots on `PollType::MultiChoice` polls.
+// Without them a ballot may select anywhere from one to all options.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct ApprovalParameters {
+    pub min_selections: u8,
+    pub max_selections: u8,
+}
+
+pub const MAX_DELEGATION_DEPTH: u8 = 5;
+
+// Balances of `mint` taken at `snapshot_slot`, published by the poll authority as the root of a
+// Merkle tree over `weight_leaf(owner, mint, balance)` (format in utils/merkle.rs). Ballot weight
+// is the proven balance, so tokens moved between wallets after the snapshot count only once.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub struct TokenWeight {
+    pub mint: Pubkey,
+    pub snapshot_slot: u64,
+    pub snapshot_root: [u8; 32],
+}
+
+impl TokenWeight {
+    pub const MAX_SIZE: usize = 32 + 8 + 32;
+}
+
+// Ballots lock at least `min_lock` tokens of `mint` into the poll's [b"escrow_vault", poll]
+// account, refunded by `unlock_tokens` once the poll is closed or cancelled. With
+// `weight_by_lock` the locked amount is the ballot's weight.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub struct TokenEscrow {
+    pub mint: Pubkey,
+    pub min_lock: u64,
+    pub weight_by_lock: bool,
+}
+
+impl TokenEscrow {
+    pub const MAX_SIZE: usize = 32 + 8 + 1;
+
+    // Amount a ballot locks, required on escrow polls and at least `min_lock`
+    pub fn validate_lock(&self, lock_amount: Option<u64>) -> Result<u64> {
+        let amount = lock_amount.ok_or(VotingError::InvalidLockAmount)?;
+        require!(amount > 0 && amount >= self.min_lock, VotingError::InvalidLockAmount);
+        Ok(amount)
+    }
+
+    // Ballot weight once `locked` tokens are in the vault, `weight` is what it would be otherwise
+    pub fn ballot_weight(&self, locked: u64, weight: u64) -> u64 {
+        if self.weight_by_lock { locked } else { weight }
+    }
+}
+
+// Balance a voter claims on a token-weighted poll, checked against `TokenWeight.snapshot_root`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct WeightProof {
+    pub balance: u64,
+    pub proof: Vec<[u8; 32]>,
+}
+
+// Proof that a voter is in the poll's allowlist (tree format in utils/merkle.rs). The member is
+// a key that signs the ballot, so a proof seen on the ledger is useless to anyone else.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct MembershipProof {
+    pub proof: Vec<[u8; 32]>,
+}
+
+// Who may vote and what a ballot is weighted by, all optional: a token gate, a balance snapshot
+// for token-weighted polls, a token escrow and an allowlist root (tree format in utils/merkle.rs)
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct PollAccessConfig {
+    pub token_gate: Option<TokenGate>,
+    pub token_weight: Option<TokenWeight>,
+    pub token_escrow: Option<TokenEscrow>,
+    pub allowlist_root: Option<[u8; 32]>,
+}
+
+// Ballot inputs only some polls take: `weight_proof` on token-weighted polls, `lock_amount` on
+// escrow polls and `membership_proof` on allowlist polls. Each must be left out on other polls.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct VoteExtras {
+    pub weight_proof: Option<WeightProof>,
+    pub lock_amount: Option<u64>,
+    pub membership_proof: Option<MembershipProof>,
+}
+
+impl MembershipProof {
+    // Checks the proof of the signing `member` against `root` and that `nullifier_hash` is the
+    // member's nullifier in `poll`
+    pub fn verify(&self, root: &[u8; 32], poll: &Pubkey, member: &Pubkey, nullifier_hash: &[u8; 32]) -> Result<()> {
+        let member = member.to_bytes();
+        require!(
+            verify_merkle_proof(member_leaf(&member), &self.proof, root),
+            VotingError::InvalidMembershipProof
+        );
+        require!(allowlist_nullifier(poll, &member) == *nullifier_hash, VotingError::InvalidNullifierHash);
+        Ok(())
+    }
+}
+
+// Restricts a poll to token holders. Membership of a collection is read from the Token-2022
+// group member extension on the NFT's mint, which only the group's update authority can set.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub enum TokenGate {
+    MinBalance { mint: Pubkey, min_amount: u64 }, // SPL Token or Token-2022 mint
+    Collection { group: Pubkey }, // Token-2022 token group of the collection
+}
+
+impl TokenGate {
+    pub const MAX_SIZE: usize = 1 + 32 + 8;
+
+    // Remaining accounts the voter passes first: their token account, then for collections
+    // the NFT's mint
+    pub fn account_count(&self) -> usize {
+        match self {
+            TokenGate::MinBalance { .. } => 1,
+            TokenGate::Collection { .. } => 2,
+        }
+    }
+
+    // Fails unless `accounts` prove that `voter` holds a qualifying token. The token account
+    // has to be owned by the voter, so a gated vote is tied to the wallet that signs it.
+    pub fn verify(&self, voter: &Pubkey, accounts: &[AccountInfo]) -> Result<()> {
+        require!(accounts.len() >= self.account_count(), VotingError::TokenGateNotMet);
+
+        let token_info = &accounts[0];
+        require!(
+            token_info.owner == &anchor_spl::token::ID || token_info.owner == &spl_token_2022::ID,
+            VotingError::InvalidTokenGateAccount
+        );
+        let token_data = token_info.try_borrow_data()?;
+        let token_account = StateWithExtensions::<TokenAccountState>::unpack(&token_data)
+            .map_err(|_| VotingError::InvalidTokenGateAccount)?
+            .base;
+        require!(token_account.owner == *voter, VotingError::InvalidTokenGateAccount);
+
+        match self {
+            TokenGate::MinBalance { mint, min_amount } => {
+                require!(token_account.mint == *mint, VotingError::InvalidTokenGateAccount);
+                require!(token_account.amount >= (*min_amount).max(1), VotingError::TokenGateNotMet);
+            },
+            TokenGate::Collection { group } => {
+                let mint_info = &accounts[1];
+                require!(
+                    mint_info.key() == token_account.mint && mint_info.owner == &spl_token_2022::ID,
+                    VotingError::InvalidTokenGateAccount
+                );
+                require!(token_account.amount >= 1, VotingError::TokenGateNotMet);
+
+                let mint_data = mint_info.try_borrow_data()?;
+                let mint = StateWithExtensions::<MintState>::unpack(&mint_data)
+                    .map_err(|_| VotingError::InvalidTokenGateAccount)?;
+                let member = mint
+                    .get_extension::<TokenGroupMember>()
+                    .map_err(|_| VotingError::TokenGateNotMet)?;
+                require!(Pubkey::from(member.group.to_bytes()) == *group, VotingError::TokenGateNotMet);
+            },
+        }
+        Ok(())
+    }
+}
+
+#[event]
+pub struct PollStatusChanged {
+    pub poll: Pubkey,
+    pub from: Option<PollStatus>, // `None` for the initial status set by `create_poll`
+    pub to: PollStatus,
+    pub reason: Option<String>,
+    pub changed_at: i64,
+}
+
+impl PollStatus {
+    // The poll lifecycle. Every status change goes through `Poll::transition`, which only
+    // allows the moves listed here:
+    //   Pending -> Active     activate_poll, once poll_begin_time is reached
+    //   Pending -> Cancelled  cancel_poll
+    //   Active  -> Cancelled  cancel_poll
+    //   Active  -> Closed     close_poll, once poll_end_time is reached
+    // create_poll starts a poll as Pending, or Active when it begins right away.
+    // Cancelled and Closed are final.
+    pub fn can_transition_to(&self, next: PollStatus) -> bool {
+        matches!(
+            (self, next),
+            (PollStatus::Pending, PollStatus::Active)
+                | (PollStatus::Pending, PollStatus::Cancelled)
+                | (PollStatus::Active, PollStatus::Cancelled)
+                | (PollStatus::Active, PollStatus::Closed)
+        )
+    }
+}
+
+// One per wallet and token-weighted poll, so a snapshot balance is only ever counted once
+#[account]
+pub struct WeightClaim {
+    pub poll: Pubkey,
+    pub owner: Pubkey,
+    pub balance: u64,
+    pub vote: Pubkey,
+    pub bump: u8,
+}
+
+impl WeightClaim {
+    pub const MAX_SIZE: usize = 8 + 32 + 32 + 8 + 32 + 1;
+}
+
+impl TokenWeight {
+    // Returns the weight `owner` may vote with
+    pub fn verify(&self, owner: &Pubkey, weight_proof: &WeightProof) -> Result<u64> {
+        require!(weight_proof.balance > 0, VotingError::InvalidVoteWeight);
+        require!(
+            verify_merkle_proof(weight_leaf(owner, &self.mint, weight_proof.balance), &weight_proof.proof, &self.snapshot_root),
+            VotingError::InvalidWeightProof
+        );
+        Ok(weight_proof.balance)
+    }
+}
+
 // implementation of PollMetadata..
 impl PollMetadata {
-    pub const MAX_SIZE: usize = 8 + 32 + 8 + 1;
+    pub const MAX_SIZE: usize = 8 + 32 + 1 + 8 + 32;
+
+    // Appends a nullifier to the accumulator and returns its sequence number
+    pub fn record_nullifier(&mut self, nullifier_hash: &[u8; 32]) -> Result<u64> {
+        let sequence = self.nullifier_count;
+        self.nullifier_accumulator = accumulate_nullifier(&self.nullifier_accumulator, nullifier_hash);
+        self.nullifier_count = self.nullifier_count
+            .checked_add(1)
+            .ok_or(VotingError::ArithmeticOverflow)?;
+        Ok(sequence)
+    }
 }
 // Poll Implementation 
 impl Poll {
     // Poll Logic
     pub const MAX_SIZE: usize = 8 + 
         32 + // author
+        1 + 32 + // pending_authority
         4 + 100 + // poll_name
         4 + (10 * (4 + 50)) + // options (maximum of 10 options, each with a maximum of 50 characters)
         4 + 500 + // description
         1 + 1 + 4 + 50 + 4 + 50 + 4 + (5 * (4 + 30)) + // eligibility_criteria
+        32 + // verifying_key
         1 + // status
         1 + // poll_type
         8 + // poll_begin_time
         8 + // poll_end_time
         1 + 8 + // max_voters
         8 + // total_votes
-        4 + (1000 * 32) + // nullifier_hashes
         4 + (10 * 8) + // vote_counts
         1 + 8 + // recast_vote_window
         8 + // unique_voters
@@ -67,7 +316,18 @@ impl Poll {
         1 + // allow_recast
         1 + // geolocation_required
         1 + (8 + 4 + (10 * 8)) + // quadratic_parameters
-        1; // bump
+        1 + 2 + // approval_parameters
+        1 + 8 + // reveal_window
+        8 + // revealed_votes
+        1 + // encrypted_ballots
+        1 + // max_delegation_depth
+        4 + (MAX_POLL_TAGS * (4 + MAX_TAG_LENGTH)) + // tags
+        1 + // is_paused
+        1 + // bump
+        1 + TokenGate::MAX_SIZE + // token_gate
+        1 + TokenWeight::MAX_SIZE + // token_weight
+        1 + TokenEscrow::MAX_SIZE + // token_escrow
+        1 + 32; // allowlist_root
 
 
     pub fn is_active(&self) -> bool {
@@ -75,8 +335,172 @@ impl Poll {
         self.status == PollStatus::Active && moment >= self.poll_begin_time && moment <= self.poll_end_time
     }
 
+    // Moves the poll to `next` if the lifecycle allows it and records the change
+    pub fn transition(&mut self, poll: Pubkey, next: PollStatus, reason: Option<String>, moment: i64) -> Result<()> {
+        require!(self.status.can_transition_to(next), VotingError::InvalidStatusTransition);
+
+        let from = self.status;
+        self.status = next;
+        self.updated_at = moment;
+
+        emit!(PollStatusChanged {
+            poll,
+            from: Some(from),
+            to: next,
+            reason,
+            changed_at: moment,
+        });
+        Ok(())
+    }
+
     pub fn can_vote(&self) -> bool {
         self.is_active() && (self.max_voters.is_none() || self.unique_voters < self.max_voters.unwrap())
     }
+
+    pub fn selection_limits(&self) -> (u8, u8) {
+        match self.approval_parameters {
+            Some(ref params) => (params.min_selections, params.max_selections),
+            None => (1, self.options.len() as u8),
+        }
+    }
+
+    pub fn is_reveal_phase(&self) -> bool {
+        let moment = Clock::get().unwrap().unix_timestamp;
+        match self.reveal_window {
+            Some(window) => moment > self.poll_end_time && moment <= self.poll_end_time.saturating_add(window),
+            None => false,
+        }
+    }
+
+    // Last moment ballots can still change the outcome
+    pub fn counting_end_time(&self) -> i64 {
+        self.poll_end_time.saturating_add(self.reveal_window.unwrap_or(0))
+    }
+
+    // Ballots whose contents are known and counted in `vote_counts` (or the encrypted tally)
+    pub fn counted_ballots(&self) -> u64 {
+        if self.reveal_window.is_some() || self.encrypted_ballots {
+            self.revealed_votes
+        } else {
+            self.unique_voters
+        }
+    }
+
+    // Credits the ballot's votes, scaled by `weight`, to the options it counts towards.
+    // Sealed ballots count for nothing until revealed.
+    pub fn record_ballot(&mut self, ballot: &Ballot, weight: u64) -> Result<()> {
+        if ballot.is_sealed() {
+            return Ok(());
+        }
+        for (option, votes) in ballot.option_votes() {
+            let added = votes.checked_mul(weight).ok_or(VotingError::ArithmeticOverflow)?;
+            let count = &mut self.vote_counts[option as usize];
+            *count = count.checked_add(added).ok_or(VotingError::ArithmeticOverflow)?;
+        }
+        self.total_votes = self.total_votes.checked_add(weight).ok_or(VotingError::ArithmeticOverflow)?;
+        Ok(())
+    }
+
+    // Reverses `record_ballot`, used when a ballot is replaced
+    pub fn remove_ballot(&mut self, ballot: &Ballot, weight: u64) -> Result<()> {
+        if ballot.is_sealed() {
+            return Ok(());
+        }
+        for (option, votes) in ballot.option_votes() {
+            let removed = votes.checked_mul(weight).ok_or(VotingError::ArithmeticOverflow)?;
+            let count = &mut self.vote_counts[option as usize];
+            *count = count.checked_sub(removed).ok_or(VotingError::ArithmeticUnderflow)?;
+        }
+        self.total_votes = self.total_votes.checked_sub(weight).ok_or(VotingError::ArithmeticUnderflow)?;
+        Ok(())
+    }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::merkle_node;
+
+    // Two-member allowlist: a regular wallet and a throwaway key registered in its place
+    fn allowlist(wallet: &Pubkey, throwaway: &Pubkey) -> ([u8; 32], [u8; 32], [u8; 32]) {
+        let wallet_leaf = member_leaf(&wallet.to_bytes());
+        let throwaway_leaf = member_leaf(&throwaway.to_bytes());
+        (merkle_node(&wallet_leaf, &throwaway_leaf), wallet_leaf, throwaway_leaf)
+    }
+
+    #[test]
+    fn escrow_lock_must_meet_minimum() {
+        let escrow = TokenEscrow { mint: Pubkey::new_unique(), min_lock: 100, weight_by_lock: false };
+
+        assert_eq!(escrow.validate_lock(Some(100)).unwrap(), 100);
+        assert_eq!(escrow.validate_lock(Some(250)).unwrap(), 250);
+        assert_eq!(escrow.validate_lock(Some(99)).unwrap_err(), error!(VotingError::InvalidLockAmount));
+        assert_eq!(escrow.validate_lock(None).unwrap_err(), error!(VotingError::InvalidLockAmount));
+
+        let no_minimum = TokenEscrow { min_lock: 0, ..escrow };
+        assert_eq!(no_minimum.validate_lock(Some(0)).unwrap_err(), error!(VotingError::InvalidLockAmount));
+    }
+
+    #[test]
+    fn escrow_weights_by_lock_only_when_configured() {
+        let escrow = TokenEscrow { mint: Pubkey::new_unique(), min_lock: 1, weight_by_lock: false };
+        assert_eq!(escrow.ballot_weight(250, 1), 1);
+
+        let weighted = TokenEscrow { weight_by_lock: true, ..escrow };
+        assert_eq!(weighted.ballot_weight(250, 1), 250);
+    }
+
+    #[test]
+    fn membership_proof_accepts_signing_members() {
+        let (poll, wallet, throwaway) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let (root, wallet_leaf, throwaway_leaf) = allowlist(&wallet, &throwaway);
+
+        let by_wallet = MembershipProof { proof: vec![throwaway_leaf] };
+        by_wallet.verify(&root, &poll, &wallet, &allowlist_nullifier(&poll, &wallet.to_bytes())).unwrap();
+
+        let by_throwaway = MembershipProof { proof: vec![wallet_leaf] };
+        by_throwaway.verify(&root, &poll, &throwaway, &allowlist_nullifier(&poll, &throwaway.to_bytes())).unwrap();
+    }
+
+    #[test]
+    fn membership_proof_rejects_outsider_and_foreign_nullifier() {
+        let (poll, wallet, throwaway) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let (root, _, throwaway_leaf) = allowlist(&wallet, &throwaway);
+        let proof = MembershipProof { proof: vec![throwaway_leaf] };
+
+        let outsider = Pubkey::new_unique();
+        assert_eq!(
+            proof.verify(&root, &poll, &outsider, &allowlist_nullifier(&poll, &outsider.to_bytes())).unwrap_err(),
+            error!(VotingError::InvalidMembershipProof)
+        );
+
+        // any nullifier but the member's own for this poll would give them a second ballot
+        let other_poll = Pubkey::new_unique();
+        assert_eq!(
+            proof.verify(&root, &poll, &wallet, &allowlist_nullifier(&other_poll, &wallet.to_bytes())).unwrap_err(),
+            error!(VotingError::InvalidNullifierHash)
+        );
+    }
+
+    // Everything a member's ballot puts on the ledger is the proof and the nullifier; replayed by
+    // someone else in another poll on the same root, neither gets them a ballot.
+    #[test]
+    fn observed_proof_cannot_be_replayed_in_another_poll() {
+        let (poll, second_poll, wallet, throwaway) =
+            (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let (root, wallet_leaf, _) = allowlist(&wallet, &throwaway);
+        let observed = MembershipProof { proof: vec![wallet_leaf] };
+        let observed_nullifier = allowlist_nullifier(&poll, &throwaway.to_bytes());
+        observed.verify(&root, &poll, &throwaway, &observed_nullifier).unwrap();
+
+        let attacker = Pubkey::new_unique();
+        assert_eq!(
+            observed.verify(&root, &second_poll, &attacker, &allowlist_nullifier(&second_poll, &throwaway.to_bytes())).unwrap_err(),
+            error!(VotingError::InvalidMembershipProof)
+        );
+        assert_eq!(
+            observed.verify(&root, &second_poll, &attacker, &observed_nullifier).unwrap_err(),
+            error!(VotingError::InvalidMembershipProof)
+        );
+    }
+}
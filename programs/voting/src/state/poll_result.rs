@@ -0,0 +1,84 @@
+use anchor_lang::prelude::*;
+use super::{Poll, PollType};
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub enum TallyMethod {
+    Plurality,     // one choice per ballot
+    Approval,      // any number of options per ballot
+    Quadratic,     // votes bought from a credit budget
+    InstantRunoff, // ranked ballots, counted in runoff rounds after close
+    Encrypted,     // homomorphic plurality, decrypted by the trustees after close
+}
+
+// Final outcome of a poll, written by `close_poll`.
+// Polls whose count finishes after close (instant-runoff, encrypted) are created with
+// `is_final == false` and completed by `tally_ranked_choice` / `publish_tally`.
+#[account]
+pub struct PollResult {
+    pub poll: Pubkey,
+    pub tally_method: TallyMethod,
+    pub vote_counts: Vec<u64>, // final round for instant-runoff
+    pub total_votes: u64,
+    pub unique_voters: u64,
+    pub counted_ballots: u64, // ballots whose contents were counted, excludes unrevealed ones
+    pub max_voters: Option<u64>,
+    pub winners: Vec<u8>, // every option sharing the top count, empty when nothing was counted
+    pub is_tie: bool,
+    pub is_final: bool,
+    pub nullifier_count: u64,
+    pub voter_set_hash: [u8; 32], // `PollMetadata.nullifier_accumulator` at close: which nullifiers voted, not their ballots
+    pub closed_at: i64,
+    pub finalized_at: Option<i64>,
+    pub bump: u8,
+}
+
+impl PollResult {
+    pub const MAX_SIZE: usize = 8 +
+        32 + // poll
+        1 + // tally_method
+        4 + (10 * 8) + // vote_counts
+        8 + // total_votes
+        8 + // unique_voters
+        8 + // counted_ballots
+        1 + 8 + // max_voters
+        4 + 10 + // winners
+        1 + // is_tie
+        1 + // is_final
+        8 + // nullifier_count
+        32 + // voter_set_hash
+        8 + // closed_at
+        1 + 8 + // finalized_at
+        1; // bump
+
+    pub fn tally_method(poll: &Poll) -> TallyMethod {
+        match poll.poll_type {
+            _ if poll.encrypted_ballots => TallyMethod::Encrypted,
+            PollType::MultiChoice => TallyMethod::Approval,
+            PollType::RankedChoice => TallyMethod::InstantRunoff,
+            PollType::Quadratic => TallyMethod::Quadratic,
+            PollType::YesNo | PollType::AnonymousFeedback | PollType::TokenWeighted => TallyMethod::Plurality,
+        }
+    }
+
+    // Every option holding the highest count; none if no votes were counted
+    pub fn leaders(vote_counts: &[u64]) -> Vec<u8> {
+        let top = vote_counts.iter().copied().max().unwrap_or(0);
+        if top == 0 {
+            return Vec::new();
+        }
+        vote_counts
+            .iter()
+            .enumerate()
+            .filter(|(_, &count)| count == top)
+            .map(|(option, _)| option as u8)
+            .collect()
+    }
+
+    pub fn finalize(&mut self, vote_counts: Vec<u64>, winners: Vec<u8>, moment: i64) {
+        self.vote_counts = vote_counts;
+        self.is_tie = winners.len() > 1;
+        self.winners = winners;
+        self.is_final = true;
+        self.finalized_at = Some(moment);
+    }
+}
@@ -0,0 +1,202 @@
+use anchor_lang::prelude::*;
+use super::Ballot;
+
+// Instant-runoff count for a closed `PollType::RankedChoice` poll.
+// Round 1 is seeded from the first preferences already in `Poll.vote_counts`; every later
+// round re-counts the `Vote` accounts in batches through `tally_ranked_choice`.
+#[account]
+pub struct RankedChoiceTally {
+    pub poll: Pubkey,
+    pub round: u8,
+    pub eliminated: u16, // bitmask over poll options
+    pub elimination_order: Vec<u8>,
+    pub round_counts: Vec<u64>,
+    pub exhausted_weight: u64, // weight of ballots with no continuing option left in this round
+    pub ballots_counted: u64,  // ballots counted in the current round
+    pub total_ballots: u64,
+    pub first_round_counts: Vec<u64>,
+    pub winner: Option<u8>,
+    pub is_complete: bool,
+    pub completed_at: Option<i64>,
+    pub bump: u8,
+}
+
+impl RankedChoiceTally {
+    pub const MAX_SIZE: usize = 8 +
+        32 + // poll
+        1 + // round
+        2 + // eliminated
+        4 + 10 + // elimination_order
+        4 + (10 * 8) + // round_counts
+        8 + // exhausted_weight
+        8 + // ballots_counted
+        8 + // total_ballots
+        4 + (10 * 8) + // first_round_counts
+        1 + 1 + // winner
+        1 + // is_complete
+        1 + 8 + // completed_at
+        1; // bump
+
+    pub fn is_eliminated(&self, option: u8) -> bool {
+        self.eliminated & (1 << option) != 0
+    }
+
+    // Highest ranked option on the ballot that is still in the count
+    pub fn next_preference(&self, ballot: &Ballot) -> Option<u8> {
+        match ballot {
+            Ballot::Ranked(rankings) => rankings.iter().copied().find(|&option| !self.is_eliminated(option)),
+            Ballot::Single(choice) => Some(*choice).filter(|&option| !self.is_eliminated(option)),
+            _ => None,
+        }
+    }
+
+    // Closes the current round once every ballot has been counted in it: either declares a
+    // winner (majority of continuing weight, or last option standing) or eliminates the
+    // weakest option and opens the next round.
+    //
+    // Ties for elimination are broken deterministically: the option with fewer first-round
+    // votes is eliminated first, then the one with the higher index.
+    pub fn finish_round(&mut self, moment: i64) {
+        let continuing: Vec<u8> = (0..self.round_counts.len() as u8)
+            .filter(|&option| !self.is_eliminated(option))
+            .collect();
+        let continuing_weight: u64 = continuing.iter().map(|&option| self.round_counts[option as usize]).sum();
+
+        let leader = continuing.iter().copied().find(|&option| {
+            self.round_counts[option as usize] as u128 * 2 > continuing_weight as u128
+        });
+
+        if let Some(winner) = leader.or(if continuing.len() == 1 { Some(continuing[0]) } else { None }) {
+            self.winner = Some(winner);
+            self.is_complete = true;
+            self.completed_at = Some(moment);
+            return;
+        }
+
+        if continuing.is_empty() || continuing_weight == 0 {
+            // no ballots at all, nothing to decide
+            self.is_complete = true;
+            self.completed_at = Some(moment);
+            return;
+        }
+
+        let weakest = continuing
+            .iter()
+            .copied()
+            .min_by_key(|&option| {
+                (
+                    self.round_counts[option as usize],
+                    self.first_round_counts[option as usize],
+                    std::cmp::Reverse(option),
+                )
+            })
+            .unwrap();
+
+        self.eliminated |= 1 << weakest;
+        self.elimination_order.push(weakest);
+        self.round += 1;
+        self.round_counts = vec![0; self.round_counts.len()];
+        self.exhausted_weight = 0;
+        self.ballots_counted = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Runs the count the way `tally_ranked_choice` does, one round per loop, with unit weights
+    fn run(options: usize, rankings: &[&[u8]]) -> RankedChoiceTally {
+        let ballots: Vec<Ballot> = rankings.iter().map(|r| Ballot::Ranked(r.to_vec())).collect();
+        let mut first_round_counts = vec![0u64; options];
+        for ballot in ballots.iter() {
+            if let Ballot::Ranked(r) = ballot {
+                first_round_counts[r[0] as usize] += 1;
+            }
+        }
+
+        let mut tally = RankedChoiceTally {
+            poll: Pubkey::new_unique(),
+            round: 1,
+            eliminated: 0,
+            elimination_order: Vec::new(),
+            round_counts: first_round_counts.clone(),
+            exhausted_weight: 0,
+            ballots_counted: ballots.len() as u64,
+            total_ballots: ballots.len() as u64,
+            first_round_counts,
+            winner: None,
+            is_complete: false,
+            completed_at: None,
+            bump: 255,
+        };
+
+        tally.finish_round(1);
+        while !tally.is_complete {
+            for ballot in ballots.iter() {
+                match tally.next_preference(ballot) {
+                    Some(option) => tally.round_counts[option as usize] += 1,
+                    None => tally.exhausted_weight += 1,
+                }
+            }
+            tally.finish_round(1);
+        }
+        tally
+    }
+
+    #[test]
+    fn first_round_majority_wins() {
+        let tally = run(3, &[&[0], &[0, 1], &[0, 2], &[1], &[2, 1]]);
+
+        assert_eq!(tally.winner, Some(0));
+        assert_eq!(tally.round, 1);
+        assert!(tally.elimination_order.is_empty());
+    }
+
+    #[test]
+    fn eliminated_ballots_transfer_to_next_preference() {
+        let tally = run(3, &[&[0], &[0], &[0], &[0], &[1, 0], &[1, 0], &[1, 0], &[2, 1], &[2, 1]]);
+
+        assert_eq!(tally.elimination_order, vec![2]);
+        assert_eq!(tally.round_counts, vec![4, 5, 0]);
+        assert_eq!(tally.winner, Some(1));
+        assert_eq!(tally.completed_at, Some(1));
+    }
+
+    #[test]
+    fn exhausted_ballots_leave_the_count() {
+        let tally = run(3, &[&[0], &[0], &[1], &[1], &[2]]);
+
+        // 2 is eliminated and its ballot ranks nothing else, so 0 and 1 stay tied on 2 each
+        assert_eq!(tally.elimination_order, vec![2, 1]);
+        assert_eq!(tally.winner, Some(0));
+        assert_eq!(tally.exhausted_weight, 3);
+    }
+
+    #[test]
+    fn elimination_tie_goes_to_fewer_first_round_votes() {
+        let mut rankings: Vec<&[u8]> = vec![&[0]; 5];
+        rankings.extend([&[1][..], &[1], &[1], &[2], &[2], &[3, 2]]);
+        let tally = run(4, &rankings);
+
+        // round 2 ties 1 and 2 on three votes, 2 had fewer first preferences
+        assert_eq!(tally.elimination_order, vec![3, 2]);
+        assert_eq!(tally.winner, Some(0));
+    }
+
+    #[test]
+    fn full_tie_eliminates_higher_index() {
+        let tally = run(2, &[&[0], &[1]]);
+
+        assert_eq!(tally.elimination_order, vec![1]);
+        assert_eq!(tally.winner, Some(0));
+    }
+
+    #[test]
+    fn no_ballots_completes_without_winner() {
+        let tally = run(3, &[]);
+
+        assert!(tally.is_complete);
+        assert_eq!(tally.winner, None);
+    }
+}
@@ -0,0 +1,127 @@
+use anchor_lang::prelude::*;
+use super::{Poll, VoteDelegation};
+
+pub const MAX_POLL_TAGS: usize = 5;
+pub const MAX_TAG_LENGTH: usize = 32;
+
+// Delegation that carries over to every poll whose tags match, keyed by
+// [b"standing_delegation", verifying_key, nullifier_hash]. Eligibility is proven once at creation
+// with the proof bound to `verifying_key` instead of a poll, so an identity holds at most one per
+// circuit and it only applies to polls that use the same circuit.
+//
+// Ballots are keyed by per-poll nullifiers, so the delegation has to land on the delegator's
+// nullifier in each poll to keep them from also voting directly. `derivation_key` is the circuit
+// proving that a per-poll nullifier and `nullifier_hash` belong to the same identity; the
+// delegator hands its nullifier key to the delegatee off-chain, and from then on the delegatee
+// applies the delegation to each new poll (`apply_standing_delegation`) without the delegator.
+// The delegatee can therefore link the delegator's ballots, never cast them. Voting directly
+// withdraws the applied delegation, or is refused once the delegatee has cast it, and
+// `override_standing_delegation` takes it back for a poll.
+#[account]
+pub struct StandingDelegation {
+    pub delegator: Pubkey,
+    pub delegatee: Pubkey,
+    pub tags: Vec<String>, // applies to polls carrying any of these tags, or to every poll if empty
+    pub verifying_key: Pubkey,
+    pub derivation_key: Pubkey,
+    pub nullifier_hash: [u8; 32],
+    pub expires_at: Option<i64>,
+    pub created_at: i64,
+    pub bump: u8,
+}
+
+impl StandingDelegation {
+    pub const MAX_SIZE: usize = 8 +
+        32 + // delegator
+        32 + // delegatee
+        4 + (MAX_POLL_TAGS * (4 + MAX_TAG_LENGTH)) + // tags
+        32 + // verifying_key
+        32 + // derivation_key
+        32 + // nullifier_hash
+        1 + 8 + // expires_at
+        8 + // created_at
+        1; // bump
+
+    pub fn applies_to(&self, poll: &Poll, moment: i64) -> bool {
+        let unexpired = self.expires_at.is_none_or(|expires_at| moment <= expires_at);
+        let tagged = self.tags.is_empty() || self.tags.iter().any(|tag| poll.tags.contains(tag));
+        unexpired && tagged && self.verifying_key == poll.verifying_key
+    }
+
+    // The regular delegation this turns into in a poll; an expiry past the poll is cut to its end
+    pub fn poll_delegation(&self, poll: Pubkey, poll_end_time: i64, moment: i64, bump: u8) -> VoteDelegation {
+        VoteDelegation {
+            poll,
+            o_delegator: self.delegator,
+            o_delegatee: self.delegatee,
+            is_active: true,
+            created_at: moment,
+            expires_at: self.expires_at.map(|expires_at| expires_at.min(poll_end_time)),
+            used_in: None,
+            bump,
+        }
+    }
+}
+
+pub fn validate_tags(tags: &[String]) -> bool {
+    tags.len() <= MAX_POLL_TAGS
+        && tags.iter().all(|tag| !tag.is_empty() && tag.len() <= MAX_TAG_LENGTH)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::VotingError;
+
+    fn standing_delegation(expires_at: Option<i64>) -> StandingDelegation {
+        StandingDelegation {
+            delegator: Pubkey::new_unique(),
+            delegatee: Pubkey::new_unique(),
+            tags: Vec::new(),
+            verifying_key: Pubkey::new_unique(),
+            derivation_key: Pubkey::new_unique(),
+            nullifier_hash: [1; 32],
+            expires_at,
+            created_at: 0,
+            bump: 255,
+        }
+    }
+
+    #[test]
+    fn poll_delegation_never_outlives_the_poll() {
+        let poll = Pubkey::new_unique();
+
+        assert_eq!(standing_delegation(None).poll_delegation(poll, 500, 10, 255).expires_at, None);
+        assert_eq!(standing_delegation(Some(400)).poll_delegation(poll, 500, 10, 255).expires_at, Some(400));
+        assert_eq!(standing_delegation(Some(900)).poll_delegation(poll, 500, 10, 255).expires_at, Some(500));
+    }
+
+    // The applied delegation sits at the delegator's [b"delegation", poll, nullifier] address,
+    // the account `cast_vote` withdraws: once the delegatee has cast it the delegator's own
+    // ballot is refused, before that it takes the delegation back.
+    #[test]
+    fn delegator_cannot_vote_after_applied_delegation_was_cast() {
+        let standing_delegation = standing_delegation(None);
+        let mut applied = standing_delegation.poll_delegation(Pubkey::new_unique(), 500, 10, 255);
+        assert_eq!(applied.o_delegator, standing_delegation.delegator);
+        assert_eq!(applied.o_delegatee, standing_delegation.delegatee);
+
+        let key = Pubkey::new_unique();
+        let mut lamports = 0;
+        let mut data = Vec::with_capacity(VoteDelegation::MAX_SIZE);
+        applied.try_serialize(&mut data).unwrap();
+        data.resize(VoteDelegation::MAX_SIZE, 0);
+        let info = AccountInfo::new(&key, false, true, &mut lamports, &mut data, &crate::ID, false, 0);
+        assert!(VoteDelegation::withdraw_for_direct_vote(&info).unwrap());
+
+        applied.used_in = Some(Pubkey::new_unique());
+        let mut data = Vec::with_capacity(VoteDelegation::MAX_SIZE);
+        applied.try_serialize(&mut data).unwrap();
+        data.resize(VoteDelegation::MAX_SIZE, 0);
+        let info = AccountInfo::new(&key, false, true, &mut lamports, &mut data, &crate::ID, false, 0);
+        assert_eq!(
+            VoteDelegation::withdraw_for_direct_vote(&info).unwrap_err(),
+            error!(VotingError::DelegationAlreadyUsed)
+        );
+    }
+}
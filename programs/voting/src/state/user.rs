@@ -29,8 +29,9 @@ impl User {
     1 + 1 + 
     8 + 
     8 + 
+    1 + 4 + 10 + // preferred_language
     1 + 32 + 
-    4 + (10 * 1) + 
+    4 + 10 + // badges_earned, one byte per badge type
     1 +
     1;
         pub fn update_activity(&mut self) -> Result<()> {
@@ -44,6 +45,33 @@ impl User {
         Ok(())
     }
 
+    // A revoked badge stops counting towards the reputation
+    pub fn remove_badge(&mut self, badge_type: BadgeType) {
+        self.badges_earned.retain(|earned| *earned != badge_type);
+        self.reputation_score = self.calculate_reputation();
+    }
+
+    // Counters only: nothing about which ballot was cast is stored on the user
+    pub fn record_vote(&mut self) -> Result<()> {
+        self.total_votes_cast = self.total_votes_cast.saturating_add(1);
+        self.update_activity()?;
+        self.reputation_score = self.calculate_reputation();
+        Ok(())
+    }
+
+    pub fn record_poll_created(&mut self) -> Result<()> {
+        self.total_polls_created = self.total_polls_created.saturating_add(1);
+        self.update_activity()?;
+        self.reputation_score = self.calculate_reputation();
+        Ok(())
+    }
+
+    pub fn record_delegation_given(&mut self) -> Result<()> {
+        self.total_delegations_given = self.total_delegations_given.saturating_add(1);
+        self.update_activity()?;
+        Ok(())
+    }
+
     pub fn calculate_reputation(&self) -> u64 {
         let base_score = self.total_votes_cast * 10;
         let creator_bonus = self.total_polls_created * 50;
@@ -62,27 +90,69 @@ pub struct UserStats  {
     pub streak_count: u32,
     pub last_streak_date: i64,
     pub civic_engagement_score: u64,
+    pub hide_voting_history: bool, // mirrors `PrivacySettings.hide_voting_history`
     pub bump: u8,
 }
 
 impl UserStats {
+    pub const MAX_POLLS_TRACKED: usize = 100;
+
     pub const MAX_SIZE: usize = 8 +
     32 + // user
-    4 + (100 * 32) + // polls_participated
+    4 + (UserStats::MAX_POLLS_TRACKED * 32) + // polls_participated
     8 + // avg_participation_time
     4 + // streak_count
     8 + // last_streak_date
     8 + // civic_engagement_score
+    1 + // hide_voting_history
     1; // bump
+
+    // Records that the user took part in `poll`, keeping the most recent polls only, unless the
+    // user hides their voting history.
+    // Streaks count consecutive days with at least one vote.
+    pub fn record_participation(&mut self, poll: Pubkey, moment: i64) {
+        if !self.hide_voting_history && !self.polls_participated.contains(&poll) {
+            if self.polls_participated.len() >= Self::MAX_POLLS_TRACKED {
+                self.polls_participated.remove(0);
+            }
+            self.polls_participated.push(poll);
+        }
+        self.record_activity(moment);
+    }
+
+    pub fn set_hide_voting_history(&mut self, hide: bool) {
+        self.hide_voting_history = hide;
+        if hide {
+            self.polls_participated.clear();
+        }
+    }
+
+    pub fn record_activity(&mut self, moment: i64) {
+        let today = moment / 86_400;
+        let last_day = self.last_streak_date / 86_400;
+        if self.streak_count == 0 || today > last_day + 1 {
+            self.streak_count = 1;
+        } else if today == last_day + 1 {
+            self.streak_count = self.streak_count.saturating_add(1);
+        }
+        self.last_streak_date = moment;
+        self.civic_engagement_score = self.civic_engagement_score.saturating_add(1);
+    }
 }
 
+// Versioned so settings can be added later without changing the account size: new fields are
+// carved out of `reserved` (which older versions leave zeroed) and bump `CURRENT_VERSION`, so
+// every version deserializes with the current layout.
 #[account]
 pub struct UserPreferences {
+    pub version: u8,
     pub user: Pubkey,
     pub notification_settings: NotificationSettings,
     pub privacy_settings: PrivacySettings,
     pub accessibility_settings: AccessibilitySettings,
+    pub updated_at: i64,
     pub bump: u8,
+    pub reserved: [u8; 32],
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone)]
@@ -110,13 +180,59 @@ pub struct AccessibilitySettings {
 }
 
 impl UserPreferences {
+    pub const CURRENT_VERSION: u8 = 1;
+
     pub const MAX_SIZE: usize = 8 +
+    1 + // version
     32 + // user
     5 + // notification_settings
     3 + // privacy_settings
     4 + // accessibility_settings
-    1; // bump
+    8 + // updated_at
+    1 + // bump
+    32; // reserved
 }
 
 
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn user(total_votes_cast: u64, badges_earned: Vec<BadgeType>) -> User {
+        User {
+            pubkey: Pubkey::new_unique(),
+            did_identifier: None,
+            total_votes_cast,
+            total_polls_created: 0,
+            total_delegations_given: 0,
+            total_delegations_received: 0,
+            reputation_score: 0,
+            is_verified: false,
+            verification_level: 0,
+            created_at: 0,
+            last_activity: 0,
+            preferred_language: None,
+            location_hash: None,
+            badges_earned,
+            is_active: true,
+            bump: 255,
+        }
+    }
+
+    #[test]
+    fn revoked_badge_stops_counting_towards_reputation() {
+        let mut user = user(3, vec![BadgeType::FirstVote, BadgeType::Delegate]);
+        user.reputation_score = user.calculate_reputation();
+        assert_eq!(user.reputation_score, 3 * 10 + 2 * 25);
+
+        user.remove_badge(BadgeType::FirstVote);
+        assert_eq!(user.badges_earned, vec![BadgeType::Delegate]);
+        assert_eq!(user.reputation_score, 3 * 10 + 25);
+
+        // revoking a badge that was never on the profile changes nothing
+        user.remove_badge(BadgeType::Auditor);
+        assert_eq!(user.reputation_score, 3 * 10 + 25);
+    }
+}
@@ -0,0 +1,38 @@
+use anchor_lang::prelude::*;
+use crate::error::*;
+use crate::utils::Groth16VerifyingKey;
+
+// Verifying key of an eligibility circuit. Seeded by [b"verifying_key", authority, circuit_id],
+// so the same key can back many polls; use the poll name as circuit id for a poll-specific key.
+// `data` holds the borsh-serialized Groth16VerifyingKey, uploaded in chunks since most keys
+// do not fit in a single transaction, and is immutable once `is_finalized` is set.
+#[account]
+pub struct VerifyingKey {
+    pub authority: Pubkey,
+    pub circuit_id: String,
+    pub public_inputs: u8,
+    pub is_finalized: bool,
+    pub created_at: i64,
+    pub finalized_at: Option<i64>,
+    pub bump: u8,
+    pub data: Vec<u8>,
+}
+
+impl VerifyingKey {
+    pub fn space(public_inputs: usize) -> usize {
+        8 +
+        32 + // authority
+        4 + 32 + // circuit_id
+        1 + // public_inputs
+        1 + // is_finalized
+        8 + // created_at
+        1 + 8 + // finalized_at
+        1 + // bump
+        4 + Groth16VerifyingKey::size(public_inputs) // data
+    }
+
+    pub fn groth16(&self) -> Result<Groth16VerifyingKey> {
+        require!(self.is_finalized, VotingError::VerifyingKeyNotFinalized);
+        Groth16VerifyingKey::try_from_slice(&self.data).map_err(|_| error!(VotingError::InvalidVerifyingKey))
+    }
+}
@@ -1,29 +1,172 @@
 use anchor_lang::prelude::*;
+use crate::error::*;
+use crate::utils::{calculate_ballot_cost, Ciphertext, MAX_ENCRYPTED_OPTIONS};
+use super::{Poll, PollType};
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq)]
+pub enum Ballot {
+    Single(u8),
+    Ranked(Vec<u8>), // option indices, most preferred first; unranked options are never counted
+    Approval(u16), // bitmask over poll options, bit i selects option i
+    Quadratic(Vec<u64>), // votes allocated to each poll option, paid for from the credit budget
+    Committed([u8; 32]), // sealed ballot on commit-reveal polls, see `ballot_commitment`
+    Encrypted(Vec<Ciphertext>), // one ElGamal ciphertext of 0 or 1 per option on encrypted polls
+}
 
 #[account]
 pub struct Vote {
     pub poll: Pubkey,
     pub nullifier_hash: [u8; 32],
-    pub vote_choice: u8,
+    pub ballot: Ballot,
     pub zk_proof_hash: [u8; 32],
     pub timestamp: i64,
-    pub delegate_from: Option<Pubkey>,
+    pub delegate_from: Vec<Pubkey>, // delegators whose power this ballot carries
     pub delegate_to: bool,
     pub vote_weight: u64,
+    pub tallied_round: u8, // last instant-runoff round this ballot was counted in, 0 while sealed
     pub bump : u8,
+    pub locked_amount: u64, // tokens held in the poll's escrow vault for this ballot
+    pub lock_owner: Option<Pubkey>, // wallet the locked tokens are refunded to
+}
+
+impl Ballot {
+    pub const MAX_SIZE: usize = 1 + 4 + (10 * 8); // variant + quadratic allocations over at most 10 options
+    pub const ENCRYPTED_MAX_SIZE: usize = 1 + 4 + (MAX_ENCRYPTED_OPTIONS * 64);
+
+    // Sealed ballots are stored but only counted once revealed (committed) or proven (encrypted)
+    pub fn is_sealed(&self) -> bool {
+        matches!(self, Ballot::Committed(_) | Ballot::Encrypted(_))
+    }
+
+    // Checks a ballot submitted while the poll is open: commit-reveal polls only take sealed
+    // ballots, every other poll only takes plain ones
+    pub fn validate(&self, poll: &Poll) -> Result<()> {
+        match self {
+            Ballot::Committed(_) => {
+                require!(poll.reveal_window.is_some(), VotingError::InvalidBallotType);
+            },
+            Ballot::Encrypted(ciphertexts) => {
+                require!(poll.encrypted_ballots, VotingError::InvalidBallotType);
+                require!(ciphertexts.len() == poll.options.len(), VotingError::InvalidVoteChoice);
+                require!(ciphertexts.iter().all(|ciphertext| ciphertext.is_valid()), VotingError::InvalidCiphertext);
+            },
+            _ => {
+                require!(poll.reveal_window.is_none(), VotingError::BallotMustBeCommitted);
+                require!(!poll.encrypted_ballots, VotingError::BallotMustBeEncrypted);
+                self.validate_contents(poll)?;
+            },
+        }
+        Ok(())
+    }
+
+    pub fn validate_contents(&self, poll: &Poll) -> Result<()> {
+        let option_count = poll.options.len();
+        match self {
+            Ballot::Single(choice) => {
+                require!(
+                    matches!(poll.poll_type, PollType::YesNo | PollType::AnonymousFeedback | PollType::TokenWeighted),
+                    VotingError::InvalidBallotType
+                );
+                require!((*choice as usize) < option_count, VotingError::InvalidVoteChoice);
+            },
+            Ballot::Ranked(rankings) => {
+                require!(poll.poll_type == PollType::RankedChoice, VotingError::InvalidBallotType);
+                require!(!rankings.is_empty() && rankings.len() <= option_count, VotingError::InvalidRanking);
+
+                let mut ranked: u16 = 0;
+                for &choice in rankings {
+                    require!((choice as usize) < option_count, VotingError::InvalidVoteChoice);
+                    require!(ranked & (1 << choice) == 0, VotingError::InvalidRanking);
+                    ranked |= 1 << choice;
+                }
+            },
+            Ballot::Approval(selections) => {
+                require!(poll.poll_type == PollType::MultiChoice, VotingError::InvalidBallotType);
+                require!(*selections >> option_count == 0, VotingError::InvalidVoteChoice);
+
+                let (min_selections, max_selections) = poll.selection_limits();
+                let selected = selections.count_ones() as u8;
+                require!(
+                    selected >= min_selections.max(1) && selected <= max_selections,
+                    VotingError::InvalidSelectionCount
+                );
+            },
+            Ballot::Quadratic(allocations) => {
+                require!(poll.poll_type == PollType::Quadratic, VotingError::InvalidBallotType);
+                require!(allocations.len() == option_count, VotingError::InvalidVoteChoice);
+                require!(allocations.iter().any(|&votes| votes > 0), VotingError::InvalidVoteWeight);
+
+                let params = poll.quadratic_parameters.as_ref().ok_or(VotingError::InvalidVoteWeight)?;
+                let cost = calculate_ballot_cost(&params.credit_cost_curve, allocations)?;
+                require!(cost <= params.max_credits, VotingError::InsufficientCredits);
+            },
+            Ballot::Committed(_) | Ballot::Encrypted(_) => return err!(VotingError::InvalidBallotType),
+        }
+        Ok(())
+    }
+
+    // Votes credited per option in `Poll.vote_counts`, before the ballot weight is applied:
+    // first preference for ranked ballots, every selected option for approval ballots and
+    // the allocated votes (not their cost) for quadratic ballots
+    pub fn option_votes(&self) -> Vec<(u8, u64)> {
+        match self {
+            Ballot::Single(choice) => vec![(*choice, 1)],
+            Ballot::Ranked(rankings) => vec![(rankings[0], 1)],
+            Ballot::Approval(selections) => (0..16u8)
+                .filter(|&option| selections & (1 << option) != 0)
+                .map(|option| (option, 1))
+                .collect(),
+            Ballot::Quadratic(allocations) => allocations
+                .iter()
+                .enumerate()
+                .filter(|(_, &votes)| votes > 0)
+                .map(|(option, &votes)| (option as u8, votes))
+                .collect(),
+            Ballot::Committed(_) | Ballot::Encrypted(_) => Vec::new(),
+        }
+    }
 }
 
 // Vote Implementation
 impl Vote {
+    pub const MAX_DELEGATORS: usize = 10;
+
     pub const MAX_SIZE: usize = 8 + 
         32 + // poll
         32 + // nullifier_hash
-        1 + // vote_choice
+        Ballot::MAX_SIZE + // ballot
         32 + // zk_proof_hash
         8 + // timestamp
-        1+ 32 + // delegate_from
+        4 + (Vote::MAX_DELEGATORS * 32) + // delegate_from
         1 + // delegate_to
-        8 ; // vote_weight
+        8 + // vote_weight
+        1 + // tallied_round
+        1 + // bump
+        8 + // locked_amount
+        1 + 32; // lock_owner
+
+    // Records tokens locked in the escrow vault for this ballot, refunded to `owner`
+    pub fn lock_tokens(&mut self, amount: u64, owner: Pubkey) {
+        self.locked_amount = amount;
+        self.lock_owner = if amount > 0 { Some(owner) } else { None };
+    }
+
+    // Clears the lock and returns the amount to refund
+    pub fn release_lock(&mut self) -> Result<u64> {
+        let amount = self.locked_amount;
+        require!(amount > 0, VotingError::NothingLocked);
+        self.locked_amount = 0;
+        Ok(amount)
+    }
+
+    // Encrypted polls need room for a ciphertext per option instead of a plaintext ballot
+    pub fn space(poll: &Poll) -> usize {
+        if poll.encrypted_ballots {
+            Self::MAX_SIZE - Ballot::MAX_SIZE + Ballot::ENCRYPTED_MAX_SIZE
+        } else {
+            Self::MAX_SIZE
+        }
+    }
 }
 
 #[account]
@@ -34,15 +177,66 @@ pub struct VoteDelegation {
     pub is_active: bool,
     pub created_at: i64,
     pub expires_at: Option<i64>,
+    pub used_in: Option<Pubkey>, // the delegatee's `Vote` once this power has been cast
+    pub bump: u8,
+}
+
+// Outgoing delegation of a wallet in a poll, keyed by [b"delegation_route", poll, delegator].
+// Lets `delegate_vote` follow a chain by wallet to reject cycles; a wallet has at most one.
+#[account]
+pub struct DelegationRoute {
+    pub poll: Pubkey,
+    pub delegator: Pubkey,
+    pub delegatee: Pubkey,
+    pub delegation: Pubkey, // the `VoteDelegation` this route belongs to
     pub bump: u8,
 }
 
+impl DelegationRoute {
+    pub const MAX_SIZE: usize = 8 + 32 + 32 + 32 + 32 + 1;
+
+    // Follows the chain that a delegation from `delegator` to `delegatee` would join and returns
+    // its length. `routes` are the route PDAs of the delegatee, its delegatee and so on, ending
+    // with the empty route PDA of the wallet that votes.
+    pub fn chain_length(
+        poll: &Pubkey,
+        delegator: &Pubkey,
+        delegatee: &Pubkey,
+        max_depth: u8,
+        routes: &[AccountInfo],
+    ) -> Result<u8> {
+        let mut current = *delegatee;
+        let mut chain_length: u8 = 1;
+        for route_info in routes.iter() {
+            let (expected, _) = Pubkey::find_program_address(
+                &[b"delegation_route", poll.as_ref(), current.as_ref()],
+                &crate::ID,
+            );
+            require!(route_info.key() == expected, VotingError::InvalidDelegationRoute);
+
+            if route_info.data_is_empty() {
+                return Ok(chain_length);
+            }
+            require!(route_info.owner == &crate::ID, VotingError::InvalidDelegationRoute);
+
+            let route = DelegationRoute::try_deserialize(&mut &route_info.try_borrow_data()?[..])?;
+            require!(route.delegatee != *delegator, VotingError::DelegationCycle);
+
+            chain_length += 1;
+            require!(chain_length <= max_depth, VotingError::DelegationTooDeep);
+            current = route.delegatee;
+        }
+        err!(VotingError::InvalidDelegationRoute)
+    }
+}
+
 #[account]
 pub struct VoterNullifier {
     pub poll: Pubkey,
     pub created_at: i64,
     pub bump: u8,
     pub nullifier_hash: [u8; 32],
+    pub sequence: u64, // position in `PollMetadata.nullifier_accumulator`
 }
 
 impl VoterNullifier {
@@ -50,10 +244,28 @@ impl VoterNullifier {
         32 + // poll
         8 + // created_at
         1 + // bump
-        32; // nullifier_hash
+        32 + // nullifier_hash
+        8; // sequence
 }
 
 impl VoteDelegation {
+    // A delegator voting directly takes their power back, as long as the delegatee has not cast
+    // it yet. `delegation_info` must be the [b"delegation", poll, nullifier] PDA of the voter;
+    // returns whether a delegation existed and was withdrawn.
+    pub fn withdraw_for_direct_vote(delegation_info: &AccountInfo) -> Result<bool> {
+        if delegation_info.data_is_empty() {
+            return Ok(false);
+        }
+        require!(delegation_info.owner == &crate::ID, VotingError::InvalidDelegationAccount);
+
+        let mut delegation = VoteDelegation::try_deserialize(&mut &delegation_info.try_borrow_data()?[..])?;
+        require!(delegation.used_in.is_none(), VotingError::DelegationAlreadyUsed);
+
+        delegation.is_active = false;
+        delegation.try_serialize(&mut &mut delegation_info.try_borrow_mut_data()?[..])?;
+        Ok(true)
+    }
+
     pub const MAX_SIZE: usize = 8 + 
         32 + // poll
         32 + // o_delegator
@@ -61,5 +273,250 @@ impl VoteDelegation {
         1 + // is_active
         8 + // created_at
         1 + 8 + // expires_at
+        1 + 32 + // used_in
         1;
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::{ApprovalParameters, QuadraticParameters};
+
+    fn delegation(used_in: Option<Pubkey>) -> VoteDelegation {
+        VoteDelegation {
+            poll: Pubkey::new_unique(),
+            o_delegator: Pubkey::new_unique(),
+            o_delegatee: Pubkey::new_unique(),
+            is_active: true,
+            created_at: 0,
+            expires_at: None,
+            used_in,
+            bump: 255,
+        }
+    }
+
+    fn serialized(delegation: &VoteDelegation) -> Vec<u8> {
+        let mut data = Vec::with_capacity(VoteDelegation::MAX_SIZE);
+        delegation.try_serialize(&mut data).unwrap();
+        data.resize(VoteDelegation::MAX_SIZE, 0);
+        data
+    }
+
+    fn vote() -> Vote {
+        Vote {
+            poll: Pubkey::new_unique(),
+            nullifier_hash: [1; 32],
+            ballot: Ballot::Single(0),
+            zk_proof_hash: [0; 32],
+            timestamp: 0,
+            delegate_from: Vec::new(),
+            delegate_to: false,
+            vote_weight: 1,
+            tallied_round: 1,
+            bump: 255,
+            locked_amount: 0,
+            lock_owner: None,
+        }
+    }
+
+    // Every field zeroed, so an open plain-ballot poll; tests fill in what they check
+    fn poll(poll_type: PollType, option_count: usize) -> Poll {
+        let mut poll = Poll::deserialize(&mut &vec![0u8; Poll::MAX_SIZE][..]).unwrap();
+        poll.poll_type = poll_type;
+        poll.options = vec![String::new(); option_count];
+        poll
+    }
+
+    #[test]
+    fn approval_ballot_respects_selection_limits() {
+        let mut poll = poll(PollType::MultiChoice, 4);
+        poll.approval_parameters = Some(ApprovalParameters { min_selections: 2, max_selections: 3 });
+
+        Ballot::Approval(0b0011).validate(&poll).unwrap();
+        Ballot::Approval(0b1101).validate(&poll).unwrap();
+        assert_eq!(Ballot::Approval(0b0100).validate(&poll).unwrap_err(), error!(VotingError::InvalidSelectionCount));
+        assert_eq!(Ballot::Approval(0b1111).validate(&poll).unwrap_err(), error!(VotingError::InvalidSelectionCount));
+        // bit 4 is past the last option
+        assert_eq!(Ballot::Approval(0b1_0001).validate(&poll).unwrap_err(), error!(VotingError::InvalidVoteChoice));
+    }
+
+    #[test]
+    fn approval_ballot_without_limits_takes_one_to_all_options() {
+        let multi_choice = poll(PollType::MultiChoice, 3);
+        Ballot::Approval(0b001).validate(&multi_choice).unwrap();
+        Ballot::Approval(0b111).validate(&multi_choice).unwrap();
+        assert_eq!(Ballot::Approval(0).validate(&multi_choice).unwrap_err(), error!(VotingError::InvalidSelectionCount));
+
+        let yes_no = poll(PollType::YesNo, 2);
+        assert_eq!(Ballot::Approval(0b01).validate(&yes_no).unwrap_err(), error!(VotingError::InvalidBallotType));
+    }
+
+    #[test]
+    fn quadratic_ballot_must_fit_the_credit_budget() {
+        let mut poll = poll(PollType::Quadratic, 3);
+        poll.quadratic_parameters = Some(QuadraticParameters { credit_cost_curve: Vec::new(), max_credits: 10 });
+
+        // 3 votes cost 9 credits, 1 vote costs 1
+        Ballot::Quadratic(vec![3, 1, 0]).validate(&poll).unwrap();
+        assert_eq!(Ballot::Quadratic(vec![3, 1, 1]).validate(&poll).unwrap_err(), error!(VotingError::InsufficientCredits));
+        assert_eq!(Ballot::Quadratic(vec![0, 0, 0]).validate(&poll).unwrap_err(), error!(VotingError::InvalidVoteWeight));
+        assert_eq!(Ballot::Quadratic(vec![1, 1]).validate(&poll).unwrap_err(), error!(VotingError::InvalidVoteChoice));
+    }
+
+    #[test]
+    fn quadratic_ballot_is_charged_by_the_custom_curve() {
+        let mut poll = poll(PollType::Quadratic, 2);
+        poll.quadratic_parameters = Some(QuadraticParameters { credit_cost_curve: vec![2, 5], max_credits: 7 });
+
+        Ballot::Quadratic(vec![2, 1]).validate(&poll).unwrap();
+        assert_eq!(Ballot::Quadratic(vec![2, 2]).validate(&poll).unwrap_err(), error!(VotingError::InsufficientCredits));
+        // the curve has no price for a third vote
+        assert_eq!(Ballot::Quadratic(vec![3, 0]).validate(&poll).unwrap_err(), error!(VotingError::InvalidVoteWeight));
+    }
+
+    // Route PDA of `wallet`, holding its delegation to `delegatee` or empty if the wallet votes
+    fn route(poll: &Pubkey, wallet: &Pubkey, delegatee: Option<&Pubkey>) -> (Pubkey, Vec<u8>) {
+        let (key, _) = Pubkey::find_program_address(&[b"delegation_route", poll.as_ref(), wallet.as_ref()], &crate::ID);
+        let mut data = Vec::new();
+        if let Some(delegatee) = delegatee {
+            let route = DelegationRoute {
+                poll: *poll,
+                delegator: *wallet,
+                delegatee: *delegatee,
+                delegation: Pubkey::new_unique(),
+                bump: 255,
+            };
+            route.try_serialize(&mut data).unwrap();
+        }
+        (key, data)
+    }
+
+    fn chain_length(
+        poll: &Pubkey,
+        delegator: &Pubkey,
+        delegatee: &Pubkey,
+        max_depth: u8,
+        mut routes: Vec<(Pubkey, Vec<u8>)>,
+    ) -> Result<u8> {
+        let mut lamports = vec![0u64; routes.len()];
+        let infos: Vec<AccountInfo> = routes
+            .iter_mut()
+            .zip(lamports.iter_mut())
+            .map(|((key, data), lamports)| AccountInfo::new(key, false, false, lamports, data, &crate::ID, false, 0))
+            .collect();
+        DelegationRoute::chain_length(poll, delegator, delegatee, max_depth, &infos)
+    }
+
+    #[test]
+    fn chain_length_counts_every_hop_to_the_voting_wallet() {
+        let (poll, delegator, a, b) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+
+        assert_eq!(chain_length(&poll, &delegator, &a, 5, vec![route(&poll, &a, None)]).unwrap(), 1);
+        let routes = vec![route(&poll, &a, Some(&b)), route(&poll, &b, None)];
+        assert_eq!(chain_length(&poll, &delegator, &a, 5, routes).unwrap(), 2);
+    }
+
+    #[test]
+    fn delegation_closing_a_cycle_is_rejected() {
+        let (poll, delegator, a, b) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+
+        let routes = vec![route(&poll, &a, Some(&b)), route(&poll, &b, Some(&delegator))];
+        assert_eq!(chain_length(&poll, &delegator, &a, 5, routes).unwrap_err(), error!(VotingError::DelegationCycle));
+    }
+
+    #[test]
+    fn chain_longer_than_max_depth_is_rejected() {
+        let (poll, delegator) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let (a, b, c) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let routes = || vec![route(&poll, &a, Some(&b)), route(&poll, &b, Some(&c)), route(&poll, &c, None)];
+
+        assert_eq!(chain_length(&poll, &delegator, &a, 3, routes()).unwrap(), 3);
+        assert_eq!(chain_length(&poll, &delegator, &a, 2, routes()).unwrap_err(), error!(VotingError::DelegationTooDeep));
+    }
+
+    #[test]
+    fn chain_must_be_given_in_order_up_to_the_voting_wallet() {
+        let (poll, delegator, a, b) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+
+        let out_of_order = vec![route(&poll, &b, None), route(&poll, &a, Some(&b))];
+        assert_eq!(
+            chain_length(&poll, &delegator, &a, 5, out_of_order).unwrap_err(),
+            error!(VotingError::InvalidDelegationRoute)
+        );
+        let cut_short = vec![route(&poll, &a, Some(&b))];
+        assert_eq!(
+            chain_length(&poll, &delegator, &a, 5, cut_short).unwrap_err(),
+            error!(VotingError::InvalidDelegationRoute)
+        );
+    }
+
+    #[test]
+    fn escrow_lock_is_released_once() {
+        let owner = Pubkey::new_unique();
+        let mut vote = vote();
+        vote.lock_tokens(500, owner);
+
+        assert_eq!(vote.lock_owner, Some(owner));
+        assert_eq!(vote.release_lock().unwrap(), 500);
+        assert_eq!(vote.locked_amount, 0);
+        assert_eq!(vote.release_lock().unwrap_err(), error!(VotingError::NothingLocked));
+    }
+
+    #[test]
+    fn ballot_without_lock_has_no_refund() {
+        let mut vote = vote();
+        vote.lock_tokens(0, Pubkey::new_unique());
+
+        assert_eq!(vote.lock_owner, None);
+        assert_eq!(vote.release_lock().unwrap_err(), error!(VotingError::NothingLocked));
+    }
+
+    #[test]
+    fn direct_vote_after_used_delegation_is_rejected() {
+        let key = Pubkey::new_unique();
+        let mut lamports = 0;
+        let mut data = serialized(&delegation(Some(Pubkey::new_unique())));
+        let info = AccountInfo::new(&key, false, true, &mut lamports, &mut data, &crate::ID, false, 0);
+
+        assert_eq!(
+            VoteDelegation::withdraw_for_direct_vote(&info).unwrap_err(),
+            error!(VotingError::DelegationAlreadyUsed)
+        );
+    }
+
+    #[test]
+    fn direct_vote_deactivates_unused_delegation() {
+        let key = Pubkey::new_unique();
+        let mut lamports = 0;
+        let mut data = serialized(&delegation(None));
+        let info = AccountInfo::new(&key, false, true, &mut lamports, &mut data, &crate::ID, false, 0);
+
+        assert!(VoteDelegation::withdraw_for_direct_vote(&info).unwrap());
+        let stored = VoteDelegation::try_deserialize(&mut &info.try_borrow_data().unwrap()[..]).unwrap();
+        assert!(!stored.is_active);
+    }
+
+    #[test]
+    fn direct_vote_without_delegation_passes() {
+        let key = Pubkey::new_unique();
+        let mut lamports = 0;
+        let mut data = Vec::new();
+        let system_program = Pubkey::default();
+        let info = AccountInfo::new(&key, false, true, &mut lamports, &mut data, &system_program, false, 0);
+
+        assert!(!VoteDelegation::withdraw_for_direct_vote(&info).unwrap());
+    }
+
+    #[test]
+    fn foreign_delegation_account_is_rejected() {
+        let key = Pubkey::new_unique();
+        let owner = Pubkey::new_unique();
+        let mut lamports = 0;
+        let mut data = serialized(&delegation(None));
+        let info = AccountInfo::new(&key, false, true, &mut lamports, &mut data, &owner, false, 0);
+
+        assert_eq!(
+            VoteDelegation::withdraw_for_direct_vote(&info).unwrap_err(),
+            error!(VotingError::InvalidDelegationAccount)
+        );
+    }
+}
@@ -1,23 +1,82 @@
 use anchor_lang::prelude::*;
+use crate::error::*;
+use super::{BadgeThresholds, Poll, MAX_ADMINS};
 
 #[account]
 pub struct Voting {
     pub authority: Pubkey,
+    pub pending_authority: Option<Pubkey>, // must accept before becoming `authority`
     pub bump: u8,
     pub created_at: i64,
     pub is_paused: bool,
+    pub paused_at: Option<i64>,
     pub total_polls: u64,
     pub total_votes: u64,
-    pub total_users: u64
+    pub total_users: u64,
+    pub admins: Vec<Pubkey>, // M-of-N admin set, empty while `authority` acts alone
+    pub threshold: u8,
+    pub timelock: i64, // seconds between a proposal's approval and its execution
+    pub governance_version: u32, // bumped whenever the admin set changes
+    pub proposal_count: u64,
+    pub poll_creation_fee: u64, // lamports paid to the config account by `create_poll`
+    pub badge_thresholds: BadgeThresholds,
 }
 
 impl Voting {
     pub const MAX_SIZE: usize = 8 +
         32 + // authority
+        1 + 32 + // pending_authority
         1 + // bump
         8 + // created_at
         1 + // is_paused
+        1 + 8 + // paused_at
         8 + // total_polls
         8 + // total_votes
-        8; // total_users
-}
\ No newline at end of file
+        8 + // total_users
+        4 + (MAX_ADMINS * 32) + // admins
+        1 + // threshold
+        8 + // timelock
+        4 + // governance_version
+        8 + // proposal_count
+        8 + // poll_creation_fee
+        BadgeThresholds::MAX_SIZE; // badge_thresholds
+
+    pub fn has_governance(&self) -> bool {
+        !self.admins.is_empty()
+    }
+
+    pub fn admin_index(&self, admin: &Pubkey) -> Option<usize> {
+        self.admins.iter().position(|candidate| candidate == admin)
+    }
+
+    pub fn pause(&mut self, moment: i64) -> Result<()> {
+        require!(!self.is_paused, VotingError::AlreadyPaused);
+        self.is_paused = true;
+        self.paused_at = Some(moment);
+        Ok(())
+    }
+
+    // Returns when the pause began
+    pub fn resume(&mut self) -> Result<Option<i64>> {
+        require!(self.is_paused, VotingError::NotPaused);
+        self.is_paused = false;
+        Ok(self.paused_at.take())
+    }
+
+    // Direct authority actions are only allowed until an admin set takes over
+    pub fn ensure_single_authority(&self, signer: &Pubkey) -> Result<()> {
+        require!(!self.has_governance(), VotingError::GovernanceRequired);
+        require!(self.authority == *signer, VotingError::UnauthorizedAccess);
+        Ok(())
+    }
+}
+
+// Shared guard for every state-changing instruction: fails while the program is paused by the
+// global authority or, when a poll is given, while that poll is paused by its authority.
+pub fn ensure_operational(voting: &Voting, poll: Option<&Poll>) -> Result<()> {
+    require!(!voting.is_paused, VotingError::SystemPaused);
+    if let Some(poll) = poll {
+        require!(!poll.is_paused, VotingError::PollPaused);
+    }
+    Ok(())
+}
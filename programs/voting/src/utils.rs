@@ -1,45 +1,162 @@
 use anchor_lang::prelude::*;
-use crate::state::*;
-use sha2::{Sha256, Digest};
 use crate::error::*;
 
 pub mod zk_verification;
 pub mod hash;
 pub mod quadratic;
+pub mod elgamal;
+pub mod merkle;
 
 pub use zk_verification::*;
 pub use hash::*;
 pub use quadratic::*;
+pub use elgamal::*;
+pub use merkle::*;
 
+// Verifies a Groth16 eligibility proof for `poll`.
+// Public inputs seen by the circuit are, in order:
+//   [nullifier_hash, poll_binding(poll), ...public_signals]
+// so a proof can neither be replayed on another poll nor under another nullifier.
 pub fn verify_zk_proof(
     proof: &[u8],
-    public_signals: &[String],
-    eligibility_criteria: &EligibilityCriteria,
+    public_signals: &[[u8; 32]],
+    verifying_key: &Groth16VerifyingKey,
+    poll: &Pubkey,
+    nullifier_hash: &[u8; 32],
 ) -> Result<bool> {
-    require!(!proof.is_empty(), VotingError::InvalidZKProof);
-    require!(!public_signals.is_empty(), VotingError::InvalidZKProof);
+    require!(!proof.is_empty(), VotingError::InvalidZkProof);
 
-    require!(proof.len() >= 32, VotingError::InvalidZKProof);
-    require!(proof.len() <= 1024, VotingError::InvalidZKProof);
+    // the nullifier PDA is seeded by raw bytes, so only the canonical encoding is accepted
+    require!(is_canonical_field_element(nullifier_hash), VotingError::InvalidNullifierHash);
 
-    // public signls validation
-        if eligibility_criteria.require_did {
-        require!(
-            public_signals.iter().any(|s| s.starts_with("did:")),
-            VotingError::ZKProofVerificationFailed
+    let proof = Groth16Proof::from_bytes(proof)?;
+
+    let mut public_inputs = Vec::with_capacity(2 + public_signals.len());
+    public_inputs.push(*nullifier_hash);
+    public_inputs.push(poll_binding(poll));
+    public_inputs.extend_from_slice(public_signals);
+
+    verify_groth16(&proof, &public_inputs, verifying_key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_bn254::prelude::{alt_bn128_addition, alt_bn128_multiplication};
+
+    // Generators of G1 and G2 in the EIP-196/197 encoding
+    fn g1() -> [u8; G1_POINT_SIZE] {
+        let mut point = [0u8; G1_POINT_SIZE];
+        point[31] = 1;
+        point[63] = 2;
+        point
+    }
+
+    fn g2() -> [u8; G2_POINT_SIZE] {
+        let hex = "198e9393920d483a7260bfb731fb5d25f1aa493335a9e71297e485b7aef312c2\
+                   1800deef121f1e76426a00665e5c4479674322d4f75edadd46debd5cd992f6ed\
+                   090689d0585ff075ec9e99ad690c3395bc4b313370b38ef355acdadcd122975b\
+                   12c85ea5db8c6deb4aab71808dcb408fe3d1e7690c43d37b4ce6cc0166fa7daa";
+        let mut point = [0u8; G2_POINT_SIZE];
+        for (i, byte) in point.iter_mut().enumerate() {
+            *byte = u8::from_str_radix(&hex[2 * i..2 * i + 2], 16).unwrap();
+        }
+        point
+    }
+
+    fn mul(point: &[u8; G1_POINT_SIZE], scalar: &[u8; 32]) -> [u8; G1_POINT_SIZE] {
+        let mut input = [0u8; G1_POINT_SIZE + 32];
+        input[..G1_POINT_SIZE].copy_from_slice(point);
+        input[G1_POINT_SIZE..].copy_from_slice(scalar);
+        alt_bn128_multiplication(&input).unwrap().try_into().unwrap()
+    }
+
+    fn add(p: &[u8; G1_POINT_SIZE], q: &[u8; G1_POINT_SIZE]) -> [u8; G1_POINT_SIZE] {
+        let mut input = [0u8; 2 * G1_POINT_SIZE];
+        input[..G1_POINT_SIZE].copy_from_slice(p);
+        input[G1_POINT_SIZE..].copy_from_slice(q);
+        alt_bn128_addition(&input).unwrap().try_into().unwrap()
+    }
+
+    fn scalar(value: u8) -> [u8; 32] {
+        let mut bytes = [0u8; 32];
+        bytes[31] = value;
+        bytes
+    }
+
+    // With beta = gamma = delta = g2 the pairing check reduces to a = alpha + vk_x + c in G1,
+    // so a valid proof can be built without a prover.
+    fn proof_for(poll: &Pubkey, nullifier_hash: &[u8; 32]) -> (Groth16VerifyingKey, Vec<u8>) {
+        let verifying_key = Groth16VerifyingKey {
+            alpha_g1: mul(&g1(), &scalar(7)),
+            beta_g2: g2(),
+            gamma_g2: g2(),
+            delta_g2: g2(),
+            ic: vec![mul(&g1(), &scalar(3)), mul(&g1(), &scalar(5)), mul(&g1(), &scalar(11))],
+        };
+
+        let vk_x = add(
+            &verifying_key.ic[0],
+            &add(&mul(&verifying_key.ic[1], nullifier_hash), &mul(&verifying_key.ic[2], &poll_binding(poll))),
         );
+        let c = mul(&g1(), &scalar(13));
+        let a = add(&add(&verifying_key.alpha_g1, &vk_x), &c);
+
+        let mut proof = Vec::with_capacity(GROTH16_PROOF_SIZE);
+        proof.extend_from_slice(&a);
+        proof.extend_from_slice(&g2());
+        proof.extend_from_slice(&c);
+        (verifying_key, proof)
+    }
+
+    #[test]
+    fn accepts_valid_proof() {
+        let poll = Pubkey::new_unique();
+        let nullifier_hash = scalar(42);
+        let (verifying_key, proof) = proof_for(&poll, &nullifier_hash);
+
+        assert!(verify_zk_proof(&proof, &[], &verifying_key, &poll, &nullifier_hash).unwrap());
+    }
+
+    #[test]
+    fn rejects_tampered_proof() {
+        let poll = Pubkey::new_unique();
+        let nullifier_hash = scalar(42);
+        let (verifying_key, mut proof) = proof_for(&poll, &nullifier_hash);
+
+        let tampered_c = add(&proof[G1_POINT_SIZE + G2_POINT_SIZE..].try_into().unwrap(), &g1());
+        proof[G1_POINT_SIZE + G2_POINT_SIZE..].copy_from_slice(&tampered_c);
+
+        assert!(!verify_zk_proof(&proof, &[], &verifying_key, &poll, &nullifier_hash).unwrap());
     }
 
-        if let Some(min_age) = eligibility_criteria.min_age {
-        // Check if age proof is included
-        let has_age_proof = public_signals.iter().any(|s| {
-            if let Ok(age) = s.parse::<u8>() {
-                age >= min_age
-            } else {
-                false
-            }
-        });
-        require!(has_age_proof, VotingError::ZKProofVerificationFailed);
+    #[test]
+    fn rejects_proof_for_another_poll_or_nullifier() {
+        let poll = Pubkey::new_unique();
+        let nullifier_hash = scalar(42);
+        let (verifying_key, proof) = proof_for(&poll, &nullifier_hash);
+
+        assert!(!verify_zk_proof(&proof, &[], &verifying_key, &Pubkey::new_unique(), &nullifier_hash).unwrap());
+        assert!(!verify_zk_proof(&proof, &[], &verifying_key, &poll, &scalar(43)).unwrap());
+    }
+
+    #[test]
+    fn rejects_malformed_input() {
+        let poll = Pubkey::new_unique();
+        let nullifier_hash = scalar(42);
+        let (verifying_key, proof) = proof_for(&poll, &nullifier_hash);
+
+        assert_eq!(
+            verify_zk_proof(&proof[1..], &[], &verifying_key, &poll, &nullifier_hash).unwrap_err(),
+            error!(VotingError::InvalidZkProof)
+        );
+        assert_eq!(
+            verify_zk_proof(&proof, &[], &verifying_key, &poll, &[0xff; 32]).unwrap_err(),
+            error!(VotingError::InvalidNullifierHash)
+        );
+        assert_eq!(
+            verify_zk_proof(&proof, &[scalar(1)], &verifying_key, &poll, &nullifier_hash).unwrap_err(),
+            error!(VotingError::InvalidPublicSignals)
+        );
     }
-    Ok(true)
-}
\ No newline at end of file
+}
@@ -0,0 +1,413 @@
+use anchor_lang::prelude::*;
+use curve25519_dalek::constants::RISTRETTO_BASEPOINT_COMPRESSED;
+use curve25519_dalek::scalar::Scalar;
+use sha2::{Sha512, Digest};
+use solana_curve25519::ristretto::{
+    add_ristretto, multiply_ristretto, multiscalar_multiply_ristretto, subtract_ristretto,
+    validate_ristretto, PodRistrettoPoint,
+};
+use solana_curve25519::scalar::PodScalar;
+use crate::error::*;
+
+// Exponential ElGamal over ristretto255 for encrypted ballots.
+// A ciphertext of m under the election key H is (c1, c2) = (r·G, m·G + r·H). Adding ciphertexts
+// adds their plaintexts, so the per-option aggregate of all ballots decrypts to the vote totals.
+// Points are compressed ristretto encodings and scalars canonical little-endian, as produced by
+// curve25519-dalek; Fiat-Shamir challenges are sha512(domain || context || points) mod l.
+
+pub const MAX_ENCRYPTED_OPTIONS: usize = 5;
+pub const MAX_TRUSTEES: usize = 5;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Ciphertext {
+    pub c1: [u8; 32],
+    pub c2: [u8; 32],
+}
+
+// Disjunctive Chaum-Pedersen proof that a ciphertext encrypts 0 or 1
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct ZeroOneProof {
+    pub challenge_0: [u8; 32],
+    pub challenge_1: [u8; 32],
+    pub response_0: [u8; 32],
+    pub response_1: [u8; 32],
+}
+
+// Chaum-Pedersen proof that log_g1(h1) == log_g2(h2)
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct EqualityProof {
+    pub challenge: [u8; 32],
+    pub response: [u8; 32],
+}
+
+fn point(bytes: &[u8; 32]) -> PodRistrettoPoint {
+    PodRistrettoPoint(*bytes)
+}
+
+fn basepoint() -> PodRistrettoPoint {
+    PodRistrettoPoint(RISTRETTO_BASEPOINT_COMPRESSED.to_bytes())
+}
+
+fn scalar(bytes: &[u8; 32]) -> Result<Scalar> {
+    Option::<Scalar>::from(Scalar::from_canonical_bytes(*bytes))
+        .ok_or(error!(VotingError::InvalidEncryptionProof))
+}
+
+fn pod(scalar: &Scalar) -> PodScalar {
+    PodScalar(scalar.to_bytes())
+}
+
+// a·p + b·q
+fn combine(a: &Scalar, p: &PodRistrettoPoint, b: &Scalar, q: &PodRistrettoPoint) -> Result<PodRistrettoPoint> {
+    multiscalar_multiply_ristretto(&[pod(a), pod(b)], &[*p, *q])
+        .ok_or(error!(VotingError::InvalidEncryptionProof))
+}
+
+fn challenge(domain: &[u8], context: &[u8], points: &[&PodRistrettoPoint]) -> Scalar {
+    let mut hasher = Sha512::new();
+    hasher.update(domain);
+    hasher.update(context);
+    for point in points {
+        hasher.update(point.0);
+    }
+    let mut wide = [0u8; 64];
+    wide.copy_from_slice(&hasher.finalize());
+    Scalar::from_bytes_mod_order_wide(&wide)
+}
+
+pub fn is_valid_point(bytes: &[u8; 32]) -> bool {
+    validate_ristretto(&point(bytes))
+}
+
+impl Ciphertext {
+    pub fn is_valid(&self) -> bool {
+        is_valid_point(&self.c1) && is_valid_point(&self.c2)
+    }
+
+    pub fn add(&self, other: &Ciphertext) -> Result<Ciphertext> {
+        let c1 = add_ristretto(&point(&self.c1), &point(&other.c1)).ok_or(VotingError::InvalidCiphertext)?;
+        let c2 = add_ristretto(&point(&self.c2), &point(&other.c2)).ok_or(VotingError::InvalidCiphertext)?;
+        Ok(Ciphertext { c1: c1.0, c2: c2.0 })
+    }
+}
+
+pub fn verify_zero_one_proof(
+    ciphertext: &Ciphertext,
+    election_key: &[u8; 32],
+    context: &[u8],
+    proof: &ZeroOneProof,
+) -> Result<()> {
+    let g = basepoint();
+    let h = point(election_key);
+    let c1 = point(&ciphertext.c1);
+    let c2 = point(&ciphertext.c2);
+    let c2_minus_g = subtract_ristretto(&c2, &g).ok_or(VotingError::InvalidCiphertext)?;
+
+    let e0 = scalar(&proof.challenge_0)?;
+    let e1 = scalar(&proof.challenge_1)?;
+    let z0 = scalar(&proof.response_0)?;
+    let z1 = scalar(&proof.response_1)?;
+
+    // m = 0: (c1, c2) = (r·G, r·H)
+    let a0 = combine(&z0, &g, &-e0, &c1)?;
+    let b0 = combine(&z0, &h, &-e0, &c2)?;
+    // m = 1: (c1, c2 - G) = (r·G, r·H)
+    let a1 = combine(&z1, &g, &-e1, &c1)?;
+    let b1 = combine(&z1, &h, &-e1, &c2_minus_g)?;
+
+    let expected = challenge(b"globalvote:zero-one", context, &[&h, &c1, &c2, &a0, &b0, &a1, &b1]);
+    require!(e0 + e1 == expected, VotingError::InvalidEncryptionProof);
+    Ok(())
+}
+
+pub fn verify_equality_proof(
+    g1: &[u8; 32],
+    h1: &[u8; 32],
+    g2: &[u8; 32],
+    h2: &[u8; 32],
+    context: &[u8],
+    proof: &EqualityProof,
+) -> Result<()> {
+    let (g1, h1, g2, h2) = (point(g1), point(h1), point(g2), point(h2));
+    let c = scalar(&proof.challenge)?;
+    let z = scalar(&proof.response)?;
+
+    let a = combine(&z, &g1, &-c, &h1)?;
+    let b = combine(&z, &g2, &-c, &h2)?;
+
+    let expected = challenge(b"globalvote:dleq", context, &[&g1, &h1, &g2, &h2, &a, &b]);
+    require!(c == expected, VotingError::InvalidEncryptionProof);
+    Ok(())
+}
+
+// The homomorphic sum of a one-of-N ballot must encrypt exactly 1:
+// (s1, s2 - G) = (r·G, r·H), i.e. log_G(s1) == log_H(s2 - G)
+pub fn verify_sum_is_one(
+    ciphertexts: &[Ciphertext],
+    election_key: &[u8; 32],
+    context: &[u8],
+    proof: &EqualityProof,
+) -> Result<()> {
+    let mut sum = Ciphertext::default();
+    for ciphertext in ciphertexts {
+        sum = sum.add(ciphertext)?;
+    }
+    let s2_minus_g = subtract_ristretto(&point(&sum.c2), &basepoint()).ok_or(VotingError::InvalidCiphertext)?;
+
+    verify_equality_proof(&basepoint().0, &sum.c1, election_key, &s2_minus_g.0, context, proof)
+}
+
+pub fn basepoint_bytes() -> [u8; 32] {
+    basepoint().0
+}
+
+// Lagrange coefficient at `at` for trustee `index` within the set `indices` (indices start at 1)
+pub fn lagrange_coefficient(index: u8, indices: &[u8], at: u8) -> Scalar {
+    let x = Scalar::from(at as u64);
+    let x_i = Scalar::from(index as u64);
+    let mut numerator = Scalar::ONE;
+    let mut denominator = Scalar::ONE;
+    for &j in indices.iter().filter(|&&j| j != index) {
+        let x_j = Scalar::from(j as u64);
+        numerator *= x - x_j;
+        denominator *= x_i - x_j;
+    }
+    numerator * denominator.invert()
+}
+
+// Evaluates the polynomial through trustee points P_i = f(i)·Q at `at`, i.e. returns f(at)·Q
+pub fn interpolate_at(points: &[(u8, [u8; 32])], at: u8) -> Result<[u8; 32]> {
+    let indices: Vec<u8> = points.iter().map(|(index, _)| *index).collect();
+    let scalars: Vec<PodScalar> = indices.iter().map(|&index| pod(&lagrange_coefficient(index, &indices, at))).collect();
+    let bases: Vec<PodRistrettoPoint> = points.iter().map(|(_, bytes)| point(bytes)).collect();
+
+    let combined = multiscalar_multiply_ristretto(&scalars, &bases).ok_or(VotingError::InvalidDecryptionShare)?;
+    Ok(combined.0)
+}
+
+// Interpolates trustee points P_i = x_i·Q into x·Q
+pub fn interpolate(points: &[(u8, [u8; 32])]) -> Result<[u8; 32]> {
+    interpolate_at(points, 0)
+}
+
+// Checks that the key shares of trustees 1..=n all lie on one polynomial of degree
+// `threshold - 1` whose value at 0 is the election key, so that any `threshold` trustees can
+// decrypt: the first `threshold` shares fix the polynomial and every later one must match it.
+pub fn verify_key_shares(key_shares: &[[u8; 32]], threshold: u8, election_key: &[u8; 32]) -> Result<bool> {
+    let points: Vec<(u8, [u8; 32])> = key_shares
+        .iter()
+        .enumerate()
+        .map(|(position, share)| (position as u8 + 1, *share))
+        .collect();
+    let (first, rest) = points.split_at(threshold as usize);
+
+    if interpolate(first)? != *election_key {
+        return Ok(false);
+    }
+    for (index, share) in rest {
+        if interpolate_at(first, *index)? != *share {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+// Checks that `total`·G == c2 - x·c1, where `decryption` is the interpolated x·c1
+pub fn verify_decrypted_total(ciphertext: &Ciphertext, decryption: &[u8; 32], total: u64) -> Result<bool> {
+    let message = subtract_ristretto(&point(&ciphertext.c2), &point(decryption)).ok_or(VotingError::InvalidCiphertext)?;
+    let claimed = multiply_ristretto(&pod(&Scalar::from(total)), &basepoint()).ok_or(VotingError::InvalidCiphertext)?;
+    Ok(message == claimed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT as G;
+    use curve25519_dalek::ristretto::{CompressedRistretto, RistrettoPoint};
+
+    const CONTEXT: &[u8] = b"poll";
+
+    fn bytes(point: &RistrettoPoint) -> [u8; 32] {
+        point.compress().to_bytes()
+    }
+
+    fn pod_point(point: &RistrettoPoint) -> PodRistrettoPoint {
+        PodRistrettoPoint(bytes(point))
+    }
+
+    fn decompress(bytes: &[u8; 32]) -> RistrettoPoint {
+        CompressedRistretto(*bytes).decompress().unwrap()
+    }
+
+    // Trustee shares of the election secret 7 on the line f(x) = 7 + 3x
+    fn trustee_secret(index: u8) -> Scalar {
+        Scalar::from(7u64) + Scalar::from(3u64) * Scalar::from(index as u64)
+    }
+
+    fn election_key() -> RistrettoPoint {
+        Scalar::from(7u64) * G
+    }
+
+    fn encrypt(message: u64, r: &Scalar) -> Ciphertext {
+        Ciphertext {
+            c1: bytes(&(r * G)),
+            c2: bytes(&(Scalar::from(message) * G + r * election_key())),
+        }
+    }
+
+    // Real branch for `message`, the other one simulated with a chosen challenge and response
+    fn prove_zero_one(message: u64, r: &Scalar, ciphertext: &Ciphertext) -> ZeroOneProof {
+        let h = election_key();
+        let c1 = decompress(&ciphertext.c1);
+        let c2 = decompress(&ciphertext.c2);
+        let (w, e_sim, z_sim) = (Scalar::from(11u64), Scalar::from(13u64), Scalar::from(17u64));
+
+        let (a0, b0, a1, b1) = if message == 1 {
+            (z_sim * G - e_sim * c1, z_sim * h - e_sim * c2, w * G, w * h)
+        } else {
+            (w * G, w * h, z_sim * G - e_sim * c1, z_sim * h - e_sim * (c2 - G))
+        };
+        let e = challenge(
+            b"globalvote:zero-one",
+            CONTEXT,
+            &[
+                &pod_point(&h), &point(&ciphertext.c1), &point(&ciphertext.c2),
+                &pod_point(&a0), &pod_point(&b0), &pod_point(&a1), &pod_point(&b1),
+            ],
+        );
+        let e_real = e - e_sim;
+        let z_real = w + e_real * r;
+
+        let (e0, e1, z0, z1) = if message == 1 {
+            (e_sim, e_real, z_sim, z_real)
+        } else {
+            (e_real, e_sim, z_real, z_sim)
+        };
+        ZeroOneProof {
+            challenge_0: e0.to_bytes(),
+            challenge_1: e1.to_bytes(),
+            response_0: z0.to_bytes(),
+            response_1: z1.to_bytes(),
+        }
+    }
+
+    // Proves log_g1(x·g1) == log_g2(x·g2)
+    fn prove_equality(x: &Scalar, g1: &RistrettoPoint, g2: &RistrettoPoint) -> EqualityProof {
+        let w = Scalar::from(19u64);
+        let (a, b) = (w * g1, w * g2);
+        let c = challenge(
+            b"globalvote:dleq",
+            CONTEXT,
+            &[
+                &pod_point(g1), &pod_point(&(x * g1)), &pod_point(g2), &pod_point(&(x * g2)),
+                &pod_point(&a), &pod_point(&b),
+            ],
+        );
+        EqualityProof { challenge: c.to_bytes(), response: (w + c * x).to_bytes() }
+    }
+
+    #[test]
+    fn zero_one_proofs_verify_for_zero_and_one() {
+        for message in [0, 1] {
+            let r = Scalar::from(5u64 + message);
+            let ciphertext = encrypt(message, &r);
+            let proof = prove_zero_one(message, &r, &ciphertext);
+
+            assert!(verify_zero_one_proof(&ciphertext, &bytes(&election_key()), CONTEXT, &proof).is_ok());
+            assert!(verify_zero_one_proof(&ciphertext, &bytes(&election_key()), b"other poll", &proof).is_err());
+        }
+    }
+
+    #[test]
+    fn zero_one_proof_rejects_other_plaintexts() {
+        let r = Scalar::from(5u64);
+        let proof = prove_zero_one(1, &r, &encrypt(1, &r));
+
+        assert_eq!(
+            verify_zero_one_proof(&encrypt(2, &r), &bytes(&election_key()), CONTEXT, &proof).unwrap_err(),
+            error!(VotingError::InvalidEncryptionProof)
+        );
+    }
+
+    #[test]
+    fn one_of_n_ballot_sums_to_one() {
+        let randomness = [Scalar::from(2u64), Scalar::from(3u64), Scalar::from(4u64)];
+        let ballot = [0, 1, 0];
+        let ciphertexts: Vec<Ciphertext> = ballot.iter().zip(randomness.iter()).map(|(&m, r)| encrypt(m, r)).collect();
+        let r_sum: Scalar = randomness.iter().sum();
+
+        let proof = prove_equality(&r_sum, &G, &election_key());
+        assert!(verify_sum_is_one(&ciphertexts, &bytes(&election_key()), CONTEXT, &proof).is_ok());
+
+        let double_vote: Vec<Ciphertext> = [1, 1, 0].iter().zip(randomness.iter()).map(|(&m, r)| encrypt(m, r)).collect();
+        assert!(verify_sum_is_one(&double_vote, &bytes(&election_key()), CONTEXT, &proof).is_err());
+    }
+
+    #[test]
+    fn trustee_shares_decrypt_the_aggregate() {
+        let votes = [1, 0, 1, 1];
+        let mut aggregate = Ciphertext::default();
+        for (i, &m) in votes.iter().enumerate() {
+            aggregate = aggregate.add(&encrypt(m, &Scalar::from(i as u64 + 2))).unwrap();
+        }
+        let c1 = decompress(&aggregate.c1);
+
+        // any two of the three trustees suffice
+        let mut shares = Vec::new();
+        for index in [1u8, 3] {
+            let secret = trustee_secret(index);
+            let share = secret * c1;
+            let proof = prove_equality(&secret, &G, &c1);
+            let key_share = bytes(&(secret * G));
+            verify_equality_proof(&basepoint_bytes(), &key_share, &aggregate.c1, &bytes(&share), CONTEXT, &proof).unwrap();
+            shares.push((index, bytes(&share)));
+        }
+
+        // key shares interpolate to the election key, decryption shares to x·c1
+        let key_shares: Vec<(u8, [u8; 32])> = [1u8, 2].iter().map(|&i| (i, bytes(&(trustee_secret(i) * G)))).collect();
+        assert_eq!(interpolate(&key_shares).unwrap(), bytes(&election_key()));
+
+        let decryption = interpolate(&shares).unwrap();
+        assert!(verify_decrypted_total(&aggregate, &decryption, 3).unwrap());
+        assert!(!verify_decrypted_total(&aggregate, &decryption, 2).unwrap());
+    }
+
+    #[test]
+    fn key_shares_must_all_lie_on_one_polynomial() {
+        let shares: Vec<[u8; 32]> = (1..=4).map(|i| bytes(&(trustee_secret(i) * G))).collect();
+        assert!(verify_key_shares(&shares, 2, &bytes(&election_key())).unwrap());
+
+        // trustee 4 is never needed to reconstruct the key, its share is still checked
+        let mut bad_trailing = shares.clone();
+        bad_trailing[3] = bytes(&(Scalar::from(99u64) * G));
+        assert!(!verify_key_shares(&bad_trailing, 2, &bytes(&election_key())).unwrap());
+
+        let mut bad_leading = shares.clone();
+        bad_leading[0] = bytes(&(Scalar::from(99u64) * G));
+        assert!(!verify_key_shares(&bad_leading, 2, &bytes(&election_key())).unwrap());
+
+        assert!(!verify_key_shares(&shares, 2, &bytes(&(Scalar::from(8u64) * G))).unwrap());
+    }
+
+    #[test]
+    fn any_threshold_subset_of_checked_shares_decrypts() {
+        let aggregate = encrypt(1, &Scalar::from(5u64)).add(&encrypt(1, &Scalar::from(6u64))).unwrap();
+        let c1 = decompress(&aggregate.c1);
+
+        for subset in [[1u8, 2], [1, 4], [3, 4]] {
+            let shares: Vec<(u8, [u8; 32])> = subset.iter().map(|&i| (i, bytes(&(trustee_secret(i) * c1)))).collect();
+            assert!(verify_decrypted_total(&aggregate, &interpolate(&shares).unwrap(), 2).unwrap());
+        }
+    }
+
+    #[test]
+    fn equality_proof_rejects_wrong_share() {
+        let secret = trustee_secret(1);
+        let c1 = Scalar::from(9u64) * G;
+        let proof = prove_equality(&secret, &G, &c1);
+        let wrong_share = (secret + Scalar::ONE) * c1;
+
+        let key_share = bytes(&(secret * G));
+        assert!(verify_equality_proof(&basepoint_bytes(), &key_share, &bytes(&c1), &bytes(&wrong_share), CONTEXT, &proof).is_err());
+    }
+}
@@ -0,0 +1,44 @@
+use anchor_lang::prelude::*;
+use sha2::{Sha256, Digest};
+use crate::state::Ballot;
+
+pub fn hash_bytes(data: &[u8]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(data);
+    hasher.finalize().into()
+}
+
+pub fn hash_zk_proof(proof: &[u8]) -> [u8; 32] {
+    hash_bytes(proof)
+}
+
+// Maps a poll address into the BN254 scalar field so circuits can bind proofs to it.
+// sha256("globalvote:poll" || poll) with the top byte cleared, which keeps it below r.
+pub fn poll_binding(poll: &Pubkey) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(b"globalvote:poll");
+    hasher.update(poll.as_ref());
+    let mut binding: [u8; 32] = hasher.finalize().into();
+    binding[0] = 0;
+    binding
+}
+
+// Running hash over a poll's spent nullifiers: acc_n = sha256("globalvote:nullifier" || acc_{n-1} || nullifier_n),
+// starting from all zeroes
+pub fn accumulate_nullifier(accumulator: &[u8; 32], nullifier_hash: &[u8; 32]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(b"globalvote:nullifier");
+    hasher.update(accumulator);
+    hasher.update(nullifier_hash);
+    hasher.finalize().into()
+}
+
+// Commitment for commit-reveal polls: sha256(borsh(ballot) || salt || nullifier_hash).
+// Binding the nullifier stops a commitment from being copied into another vote.
+pub fn ballot_commitment(ballot: &Ballot, salt: &[u8; 32], nullifier_hash: &[u8; 32]) -> Result<[u8; 32]> {
+    let mut hasher = Sha256::new();
+    hasher.update(ballot.try_to_vec()?);
+    hasher.update(salt);
+    hasher.update(nullifier_hash);
+    Ok(hasher.finalize().into())
+}
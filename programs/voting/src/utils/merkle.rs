@@ -0,0 +1,166 @@
+use anchor_lang::prelude::*;
+use sha2::{Sha256, Digest};
+
+// Merkle trees published on polls (balance snapshots and voter allowlists) share one format
+// so a single off-chain tool can build them:
+//   - leaf nodes are sha256(0x00 || leaf data), see the `*_leaf` functions for the data
+//   - inner nodes are sha256(0x01 || min(a, b) || max(a, b)), pairs ordered bytewise so proofs
+//     need no left/right flags
+//   - a node without a sibling is carried up to the next level unchanged
+//   - a proof lists the sibling hashes from the leaf up to, not including, the root
+// The prefixes keep a leaf from being passed off as an inner node.
+pub const MAX_MERKLE_PROOF_LENGTH: usize = 32;
+
+pub fn merkle_leaf(data: &[u8]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update([0u8]);
+    hasher.update(data);
+    hasher.finalize().into()
+}
+
+pub fn merkle_node(a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
+    let (first, second) = if a <= b { (a, b) } else { (b, a) };
+    let mut hasher = Sha256::new();
+    hasher.update([1u8]);
+    hasher.update(first);
+    hasher.update(second);
+    hasher.finalize().into()
+}
+
+pub fn verify_merkle_proof(leaf: [u8; 32], proof: &[[u8; 32]], root: &[u8; 32]) -> bool {
+    if proof.len() > MAX_MERKLE_PROOF_LENGTH {
+        return false;
+    }
+    let computed = proof.iter().fold(leaf, |node, sibling| merkle_node(&node, sibling));
+    computed == *root
+}
+
+// Allowlist leaf: the 32-byte public key that signs the member's ballots. Members who do not
+// want their wallet on the list register a throwaway ed25519 key and vote from any fee payer.
+pub fn member_leaf(member: &[u8; 32]) -> [u8; 32] {
+    merkle_leaf(member)
+}
+
+// Nullifier of an allowlisted member in a poll: sha256("globalvote:allowlist" || poll || member)
+// with the top byte cleared like ZK nullifiers. Deterministic, so each member votes once.
+pub fn allowlist_nullifier(poll: &Pubkey, member: &[u8; 32]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(b"globalvote:allowlist");
+    hasher.update(poll.as_ref());
+    hasher.update(member);
+    let mut nullifier: [u8; 32] = hasher.finalize().into();
+    nullifier[0] = 0;
+    nullifier
+}
+
+// Balance snapshot leaf: owner (32) || mint (32) || balance (u64 little-endian)
+pub fn weight_leaf(owner: &Pubkey, mint: &Pubkey, balance: u64) -> [u8; 32] {
+    let mut data = Vec::with_capacity(72);
+    data.extend_from_slice(owner.as_ref());
+    data.extend_from_slice(mint.as_ref());
+    data.extend_from_slice(&balance.to_le_bytes());
+    merkle_leaf(&data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Builds the tree level by level and returns the root and every leaf's proof
+    fn build(leaves: &[[u8; 32]]) -> ([u8; 32], Vec<Vec<[u8; 32]>>) {
+        let mut proofs = vec![Vec::new(); leaves.len()];
+        let mut positions: Vec<usize> = (0..leaves.len()).collect();
+        let mut level = leaves.to_vec();
+
+        while level.len() > 1 {
+            for (leaf, position) in positions.iter_mut().enumerate() {
+                if let Some(sibling) = level.get(*position ^ 1) {
+                    proofs[leaf].push(*sibling);
+                }
+                *position /= 2;
+            }
+            level = level
+                .chunks(2)
+                .map(|pair| if pair.len() == 2 { merkle_node(&pair[0], &pair[1]) } else { pair[0] })
+                .collect();
+        }
+        (level[0], proofs)
+    }
+
+    fn members(count: u8) -> Vec<[u8; 32]> {
+        (0..count).map(|i| member_leaf(&[i; 32])).collect()
+    }
+
+    #[test]
+    fn accepts_every_member_of_an_odd_sized_tree() {
+        let leaves = members(5);
+        let (root, proofs) = build(&leaves);
+
+        for (leaf, proof) in leaves.iter().zip(proofs.iter()) {
+            assert!(verify_merkle_proof(*leaf, proof, &root));
+        }
+    }
+
+    #[test]
+    fn accepts_single_leaf_tree_with_empty_proof() {
+        let leaves = members(1);
+        let (root, _) = build(&leaves);
+
+        assert_eq!(root, leaves[0]);
+        assert!(verify_merkle_proof(leaves[0], &[], &root));
+    }
+
+    #[test]
+    fn rejects_non_member_and_altered_proof() {
+        let leaves = members(4);
+        let (root, proofs) = build(&leaves);
+
+        assert!(!verify_merkle_proof(member_leaf(&[9; 32]), &proofs[0], &root));
+        assert!(!verify_merkle_proof(leaves[0], &proofs[1], &root));
+
+        let mut altered = proofs[0].clone();
+        altered[0][0] ^= 1;
+        assert!(!verify_merkle_proof(leaves[0], &altered, &root));
+        assert!(!verify_merkle_proof(leaves[0], &proofs[0], &[0; 32]));
+    }
+
+    #[test]
+    fn rejects_inner_node_passed_as_leaf() {
+        let leaves = members(4);
+        let (root, proofs) = build(&leaves);
+
+        // the parent of leaves 0 and 1 verifies with the upper half of their proof as a node,
+        // but no leaf data hashes to it
+        let parent = merkle_node(&leaves[0], &leaves[1]);
+        assert!(verify_merkle_proof(parent, &proofs[0][1..], &root));
+        assert!(!verify_merkle_proof(merkle_leaf(&[leaves[0], leaves[1]].concat()), &proofs[0][1..], &root));
+    }
+
+    #[test]
+    fn rejects_overlong_proof() {
+        let leaf = member_leaf(&[1; 32]);
+        let proof = vec![[2u8; 32]; MAX_MERKLE_PROOF_LENGTH + 1];
+        let root = proof.iter().fold(leaf, |node, sibling| merkle_node(&node, sibling));
+
+        assert!(!verify_merkle_proof(leaf, &proof, &root));
+    }
+
+    #[test]
+    fn weight_leaf_binds_owner_mint_and_balance() {
+        let (owner, mint) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let leaf = weight_leaf(&owner, &mint, 100);
+
+        assert_ne!(leaf, weight_leaf(&owner, &mint, 101));
+        assert_ne!(leaf, weight_leaf(&mint, &owner, 100));
+    }
+
+    #[test]
+    fn allowlist_nullifier_is_canonical_and_per_poll() {
+        let member = Pubkey::new_unique().to_bytes();
+        let (poll, other_poll) = (Pubkey::new_unique(), Pubkey::new_unique());
+
+        assert_eq!(allowlist_nullifier(&poll, &member)[0], 0);
+        assert_eq!(allowlist_nullifier(&poll, &member), allowlist_nullifier(&poll, &member));
+        assert_ne!(allowlist_nullifier(&poll, &member), allowlist_nullifier(&other_poll, &member));
+    }
+}
@@ -0,0 +1,76 @@
+use anchor_lang::prelude::*;
+use crate::error::*;
+
+// Cost in credits of casting `votes` votes on a single option.
+// With an empty curve the cost is votes^2; otherwise `credit_cost_curve[k - 1]` is the total
+// cost of k votes and allocations beyond the end of the curve are rejected.
+pub fn calculate_quadratic_cost(credit_cost_curve: &[u64], votes: u64) -> Result<u64> {
+    if votes == 0 {
+        return Ok(0);
+    }
+
+    if credit_cost_curve.is_empty() {
+        return votes.checked_mul(votes).ok_or(VotingError::ArithmeticOverflow.into());
+    }
+
+    let index = usize::try_from(votes - 1).map_err(|_| VotingError::InvalidVoteWeight)?;
+    credit_cost_curve.get(index).copied().ok_or(VotingError::InvalidVoteWeight.into())
+}
+
+// Total cost of a ballot spreading `allocations[i]` votes on option i
+pub fn calculate_ballot_cost(credit_cost_curve: &[u64], allocations: &[u64]) -> Result<u64> {
+    allocations.iter().try_fold(0u64, |total, &votes| -> Result<u64> {
+        let cost = calculate_quadratic_cost(credit_cost_curve, votes)?;
+        total.checked_add(cost).ok_or(VotingError::ArithmeticOverflow.into())
+    })
+}
+
+// A custom curve must charge at least as much for every additional vote
+pub fn validate_cost_curve(credit_cost_curve: &[u64]) -> Result<()> {
+    require!(credit_cost_curve.len() <= 10, VotingError::InvalidVoteWeight);
+    require!(
+        credit_cost_curve.first().is_none_or(|&cost| cost > 0),
+        VotingError::InvalidVoteWeight
+    );
+    require!(
+        credit_cost_curve.windows(2).all(|pair| pair[1] >= pair[0]),
+        VotingError::InvalidVoteWeight
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cost_is_the_square_without_a_curve() {
+        assert_eq!(calculate_quadratic_cost(&[], 0).unwrap(), 0);
+        assert_eq!(calculate_quadratic_cost(&[], 1).unwrap(), 1);
+        assert_eq!(calculate_quadratic_cost(&[], 4).unwrap(), 16);
+        assert_eq!(
+            calculate_quadratic_cost(&[], u64::MAX).unwrap_err(),
+            error!(VotingError::ArithmeticOverflow)
+        );
+    }
+
+    #[test]
+    fn cost_follows_a_custom_curve_up_to_its_end() {
+        let curve = [1, 3, 6];
+        assert_eq!(calculate_quadratic_cost(&curve, 0).unwrap(), 0);
+        assert_eq!(calculate_quadratic_cost(&curve, 3).unwrap(), 6);
+        assert_eq!(calculate_quadratic_cost(&curve, 4).unwrap_err(), error!(VotingError::InvalidVoteWeight));
+
+        assert_eq!(calculate_ballot_cost(&curve, &[2, 0, 1]).unwrap(), 4);
+        assert_eq!(calculate_ballot_cost(&[], &[2, 0, 3]).unwrap(), 13);
+    }
+
+    #[test]
+    fn custom_curve_must_not_get_cheaper() {
+        validate_cost_curve(&[]).unwrap();
+        validate_cost_curve(&[1, 1, 4]).unwrap();
+        assert_eq!(validate_cost_curve(&[0, 1]).unwrap_err(), error!(VotingError::InvalidVoteWeight));
+        assert_eq!(validate_cost_curve(&[2, 1]).unwrap_err(), error!(VotingError::InvalidVoteWeight));
+        assert_eq!(validate_cost_curve(&[1; 11]).unwrap_err(), error!(VotingError::InvalidVoteWeight));
+    }
+}
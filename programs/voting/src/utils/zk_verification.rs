@@ -0,0 +1,165 @@
+use anchor_lang::prelude::*;
+use solana_bn254::prelude::{alt_bn128_addition, alt_bn128_multiplication, alt_bn128_pairing};
+use crate::error::*;
+
+// Groth16 verification over BN254 (alt_bn128) using the native syscalls.
+// All points and field elements are big-endian, in the same layout as
+// Ethereum's precompiles (EIP-196/197):
+//   G1 = x (32) || y (32)
+//   G2 = x_c1 (32) || x_c0 (32) || y_c1 (32) || y_c0 (32)
+//
+// Serialized proof layout: a (G1) || b (G2) || c (G1) = 256 bytes.
+// `a` is sent as produced by the prover; it is negated on-chain.
+
+pub const FIELD_ELEMENT_SIZE: usize = 32;
+pub const G1_POINT_SIZE: usize = 64;
+pub const G2_POINT_SIZE: usize = 128;
+pub const GROTH16_PROOF_SIZE: usize = G1_POINT_SIZE + G2_POINT_SIZE + G1_POINT_SIZE;
+
+// Maximum number of public inputs a poll circuit may expose
+// (nullifier hash and poll binding included).
+pub const MAX_PUBLIC_INPUTS: usize = 16;
+
+// BN254 base field modulus q
+const BASE_FIELD_MODULUS: [u8; 32] = [
+    0x30, 0x64, 0x4e, 0x72, 0xe1, 0x31, 0xa0, 0x29, 0xb8, 0x50, 0x45, 0xb6, 0x81, 0x81, 0x58, 0x5d,
+    0x97, 0x81, 0x6a, 0x91, 0x68, 0x71, 0xca, 0x8d, 0x3c, 0x20, 0x8c, 0x16, 0xd8, 0x7c, 0xfd, 0x47,
+];
+
+// BN254 scalar field modulus r, every public input must be strictly below it
+const SCALAR_FIELD_MODULUS: [u8; 32] = [
+    0x30, 0x64, 0x4e, 0x72, 0xe1, 0x31, 0xa0, 0x29, 0xb8, 0x50, 0x45, 0xb6, 0x81, 0x81, 0x58, 0x5d,
+    0x28, 0x33, 0xe8, 0x48, 0x79, 0xb9, 0x70, 0x91, 0x43, 0xe1, 0xf5, 0x93, 0xf0, 0x00, 0x00, 0x01,
+];
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct Groth16VerifyingKey {
+    pub alpha_g1: [u8; G1_POINT_SIZE],
+    pub beta_g2: [u8; G2_POINT_SIZE],
+    pub gamma_g2: [u8; G2_POINT_SIZE],
+    pub delta_g2: [u8; G2_POINT_SIZE],
+    pub ic: Vec<[u8; G1_POINT_SIZE]>, // one entry per public input, plus the constant term
+}
+
+impl Groth16VerifyingKey {
+    pub fn size(public_inputs: usize) -> usize {
+        G1_POINT_SIZE + // alpha_g1
+        3 * G2_POINT_SIZE + // beta_g2, gamma_g2, delta_g2
+        4 + (public_inputs + 1) * G1_POINT_SIZE // ic
+    }
+
+    pub fn public_inputs_len(&self) -> usize {
+        self.ic.len().saturating_sub(1)
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        require!(
+            self.ic.len() >= 3 && self.ic.len() <= MAX_PUBLIC_INPUTS + 1,
+            VotingError::InvalidVerifyingKey
+        );
+        Ok(())
+    }
+}
+
+pub struct Groth16Proof {
+    pub a: [u8; G1_POINT_SIZE],
+    pub b: [u8; G2_POINT_SIZE],
+    pub c: [u8; G1_POINT_SIZE],
+}
+
+impl Groth16Proof {
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        require!(bytes.len() == GROTH16_PROOF_SIZE, VotingError::InvalidZkProof);
+
+        let mut proof = Groth16Proof {
+            a: [0u8; G1_POINT_SIZE],
+            b: [0u8; G2_POINT_SIZE],
+            c: [0u8; G1_POINT_SIZE],
+        };
+        proof.a.copy_from_slice(&bytes[..G1_POINT_SIZE]);
+        proof.b.copy_from_slice(&bytes[G1_POINT_SIZE..G1_POINT_SIZE + G2_POINT_SIZE]);
+        proof.c.copy_from_slice(&bytes[G1_POINT_SIZE + G2_POINT_SIZE..]);
+        Ok(proof)
+    }
+}
+
+pub fn is_canonical_field_element(value: &[u8; FIELD_ELEMENT_SIZE]) -> bool {
+    value.as_slice() < SCALAR_FIELD_MODULUS.as_slice()
+}
+
+fn negate_g1(point: &[u8; G1_POINT_SIZE]) -> Result<[u8; G1_POINT_SIZE]> {
+    let mut negated = *point;
+
+    // The point at infinity is its own negation
+    if point.iter().all(|&b| b == 0) {
+        return Ok(negated);
+    }
+
+    let y = &point[FIELD_ELEMENT_SIZE..];
+    require!(y < BASE_FIELD_MODULUS.as_slice(), VotingError::InvalidZkProof);
+
+    // y' = q - y, big-endian with borrow
+    let mut borrow = 0i16;
+    for i in (0..FIELD_ELEMENT_SIZE).rev() {
+        let mut diff = BASE_FIELD_MODULUS[i] as i16 - y[i] as i16 - borrow;
+        borrow = if diff < 0 {
+            diff += 256;
+            1
+        } else {
+            0
+        };
+        negated[FIELD_ELEMENT_SIZE + i] = diff as u8;
+    }
+    Ok(negated)
+}
+
+pub fn verify_groth16(
+    proof: &Groth16Proof,
+    public_inputs: &[[u8; FIELD_ELEMENT_SIZE]],
+    verifying_key: &Groth16VerifyingKey,
+) -> Result<bool> {
+    require!(
+        public_inputs.len() == verifying_key.public_inputs_len(),
+        VotingError::InvalidPublicSignals
+    );
+
+    // vk_x = ic[0] + sum(public_inputs[i] * ic[i + 1])
+    let mut vk_x = verifying_key.ic[0];
+    for (input, ic) in public_inputs.iter().zip(verifying_key.ic.iter().skip(1)) {
+        require!(is_canonical_field_element(input), VotingError::InvalidPublicSignals);
+
+        let mut mul_input = [0u8; G1_POINT_SIZE + FIELD_ELEMENT_SIZE];
+        mul_input[..G1_POINT_SIZE].copy_from_slice(ic);
+        mul_input[G1_POINT_SIZE..].copy_from_slice(input);
+        let product = alt_bn128_multiplication(&mul_input)
+            .map_err(|_| VotingError::InvalidVerifyingKey)?;
+
+        let mut add_input = [0u8; 2 * G1_POINT_SIZE];
+        add_input[..G1_POINT_SIZE].copy_from_slice(&vk_x);
+        add_input[G1_POINT_SIZE..].copy_from_slice(&product);
+        let sum = alt_bn128_addition(&add_input)
+            .map_err(|_| VotingError::InvalidVerifyingKey)?;
+        vk_x.copy_from_slice(&sum);
+    }
+
+    // e(-a, b) * e(alpha, beta) * e(vk_x, gamma) * e(c, delta) == 1
+    let neg_a = negate_g1(&proof.a)?;
+    let pairs: [(&[u8], &[u8]); 4] = [
+        (&neg_a, &proof.b),
+        (&verifying_key.alpha_g1, &verifying_key.beta_g2),
+        (&vk_x, &verifying_key.gamma_g2),
+        (&proof.c, &verifying_key.delta_g2),
+    ];
+
+    let mut pairing_input = Vec::with_capacity(4 * (G1_POINT_SIZE + G2_POINT_SIZE));
+    for (g1, g2) in pairs.iter() {
+        pairing_input.extend_from_slice(g1);
+        pairing_input.extend_from_slice(g2);
+    }
+
+    let result = alt_bn128_pairing(&pairing_input).map_err(|_| VotingError::InvalidZkProof)?;
+
+    let mut expected = [0u8; FIELD_ELEMENT_SIZE];
+    expected[FIELD_ELEMENT_SIZE - 1] = 1;
+    Ok(result.as_slice() == expected.as_slice())
+}